@@ -0,0 +1,8 @@
+use sha2::{Digest, Sha256};
+
+fn main() {
+    let path = "src/config/master_data.json";
+    println!("cargo:rerun-if-changed={path}");
+    let contents = std::fs::read(path).expect("failed to read the embedded master catalogue");
+    println!("cargo:rustc-env=MMS_MASTER_SHA256={:x}", Sha256::digest(contents));
+}
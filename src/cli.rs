@@ -1,4 +1,6 @@
+use crate::config::schema::SchemaKind;
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 /// Manage MCP Servers (mms) CLI.
 #[derive(Debug, Parser)]
@@ -10,6 +12,18 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Load placeholder values from a dotenv file (repeatable; later files win).
+    #[arg(long = "env-file", global = true, value_name = "PATH")]
+    pub env_files: Vec<PathBuf>,
+
+    /// Print secret values instead of masking them.
+    #[arg(long = "show-secrets", global = true)]
+    pub show_secrets: bool,
+
+    /// Replace an unreadable master catalogue with the embedded copy without asking.
+    #[arg(long, global = true)]
+    pub repair: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -24,15 +38,18 @@ pub enum Commands {
         from_global: bool,
     },
 
-    /// List MCP servers available in the global catalogue.
+    /// List available MCP servers and the catalogue layer each comes from.
     #[command(visible_aliases = ["ls"])]
     List,
 
-    /// Add servers from the global catalogue into the project-local file.
+    /// Add servers from the master, team or global catalogues into the project-local file.
     #[command(visible_aliases = ["a"])]
     Add {
         /// Server names to add.
         names: Vec<String>,
+        /// Reference the global entries via `extends` instead of copying them.
+        #[arg(long)]
+        link: bool,
     },
 
     /// Remove a server from the project-local catalogue.
@@ -42,6 +59,19 @@ pub enum Commands {
         name: String,
     },
 
+    /// Find servers by name, description, tag or package across all catalogue layers.
+    Search {
+        /// Words to look for; every word has to match.
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+
+    /// Show everything known about a server: definition, variables, scopes and sync targets.
+    Show {
+        /// Server name to inspect.
+        name: String,
+    },
+
     /// Show the command used to start a server.
     #[command(visible_aliases = ["cmd"])]
     Command {
@@ -63,6 +93,94 @@ pub enum Commands {
         skip_gemini: bool,
     },
 
+    /// Pin floating npm, PyPI and git packages to exact versions in `.mcp.lock`.
+    Lock {
+        /// Re-resolve this server even if its pin is still current (repeatable).
+        #[arg(long, value_name = "NAME")]
+        update: Vec<String>,
+    },
+
+    /// Check a catalogue for structural and semantic problems.
+    Validate {
+        /// Catalogue file to check (defaults to the nearest `.mcp.json`).
+        path: Option<PathBuf>,
+    },
+
+    /// Report the variables each server needs and whether they are set.
+    Env {
+        /// Inspect the global `~/.mcp.json` instead of the project catalogue.
+        #[arg(long)]
+        global: bool,
+        /// Exit with an error when a required variable is missing.
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Report whether the master, global catalogue and sync targets are up to date.
+    Status,
+
+    /// Print the JSON Schema for catalogue files.
+    Schema {
+        /// Catalogue kind to describe.
+        #[arg(long, value_enum, default_value_t = SchemaKind::Local)]
+        kind: SchemaKind,
+    },
+
+    /// Upgrade catalogue files written by older mms builds to the current schema.
+    Migrate {
+        /// Report files that need upgrading without modifying them.
+        #[arg(long)]
+        check: bool,
+        /// Catalogue files to migrate (defaults to the local, global and master catalogues).
+        paths: Vec<PathBuf>,
+    },
+
+    /// Maintain the global `~/.mcp.json` catalogue.
+    Global {
+        #[command(subcommand)]
+        action: GlobalAction,
+    },
+
+    /// Update or reset the master catalogue that seeds `~/.mcp.json`.
+    Master {
+        #[command(subcommand)]
+        action: MasterAction,
+    },
+
+    /// Convert MCP registry `server.json` documents into catalogue entries.
+    Import {
+        /// A `server.json` file, or a directory searched for `.json` files.
+        #[arg(long, value_name = "FILE|DIR")]
+        registry: PathBuf,
+        /// Catalogue to add the entries to (defaults to the nearest `.mcp.json`).
+        #[arg(long, value_name = "FILE")]
+        into: Option<PathBuf>,
+        /// Print the converted entries instead of writing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Approve the current definition of servers after reviewing what changed.
+    Trust {
+        #[arg(required = true, value_name = "NAME")]
+        names: Vec<String>,
+    },
+
+    /// Subscribe to git-hosted team catalogues and keep them up to date.
+    Catalogue {
+        #[command(subcommand)]
+        action: CatalogueAction,
+    },
+
+    /// Manage secrets in the encrypted store used by `${secret:NAME}` placeholders.
+    Secret {
+        /// Unlock the store with a key file instead of a passphrase.
+        #[arg(long = "key-file", value_name = "PATH", global = true)]
+        key_file: Option<PathBuf>,
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+
     /// Remove generated configuration artifacts.
     #[command(visible_aliases = ["cln"])]
     Clean {
@@ -71,6 +189,88 @@ pub enum Commands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum GlobalAction {
+    /// Merge master catalogue changes into `~/.mcp.json`, keeping local edits.
+    Upgrade {
+        /// Ask how to resolve each conflict instead of keeping the local version.
+        #[arg(long)]
+        interactive: bool,
+        /// Report what would change without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MasterAction {
+    /// Install a new master catalogue from a file or an http(s) URL.
+    Update {
+        /// Path or URL of the catalogue to install.
+        #[arg(long = "from", value_name = "PATH|URL")]
+        source: String,
+        /// Refuse the catalogue unless its SHA-256 digest matches.
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+        /// Show the differences without installing.
+        #[arg(long)]
+        dry_run: bool,
+        /// Accept a catalogue without a signature from a trusted key, with a warning.
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// Restore the master catalogue embedded in this build.
+    Reset,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CatalogueAction {
+    /// Clone a team catalogue repository and add it as a team layer.
+    Subscribe {
+        /// Git URL or path of a repository with `.mcp.json` at its root.
+        #[arg(value_name = "GIT-URL|PATH")]
+        source: String,
+        /// Branch, tag or commit to follow instead of the default branch.
+        #[arg(long = "ref", value_name = "REF")]
+        git_ref: Option<String>,
+        /// Layer name; defaults to the repository name.
+        #[arg(long)]
+        name: Option<String>,
+        /// Accept a catalogue without a signature from a trusted key, with a warning.
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// Fetch subscribed catalogues and show what changed.
+    Pull {
+        /// Only pull this subscription.
+        name: Option<String>,
+        /// Accept a catalogue without a signature from a trusted key, with a warning.
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// List team catalogues and the revision each is at.
+    #[command(visible_aliases = ["ls"])]
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SecretAction {
+    /// Store a secret, reading the value from a prompt or stdin unless `--value` is given.
+    Set {
+        name: String,
+        /// Secret value (visible in shell history; prefer the prompt).
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// Print a stored secret.
+    Get { name: String },
+    /// List stored secret names.
+    #[command(visible_aliases = ["ls"])]
+    List,
+    /// Delete a stored secret.
+    Rm { name: String },
+}
+
 #[derive(Debug, Clone, Args)]
 pub struct CleanSelection {
     /// Remove everything (local, Gemini, Codex, global, master).
@@ -1,19 +1,27 @@
-use super::master::MasterCatalogue;
+use super::master::{MasterCatalogue, Recovery};
+use super::merge::{self, MergeOutcome};
+use super::migrate::{read_catalogue, write_catalogue};
 use super::model::{McpCatalogue, McpServer};
 use super::paths::MmsPaths;
+use super::placeholder::{self, VariableSource, VariablesOnly};
+use super::settings::PlaceholderMode;
 use crate::error::AppError;
-use std::fs;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 
 /// Manager for the user-wide `~/.mcp.json` catalogue.
 pub struct GlobalCatalogue;
 
 impl GlobalCatalogue {
-    pub fn ensure(paths: &MmsPaths) -> Result<McpCatalogue, AppError> {
+    pub fn ensure(
+        paths: &MmsPaths,
+        mode: PlaceholderMode,
+        vars: &dyn VariableSource,
+        recovery: Recovery,
+    ) -> Result<McpCatalogue, AppError> {
         let path = paths.global_catalogue_path();
         if !path.exists() {
-            MasterCatalogue::write_embedded(paths)?;
-            Self::write_from_master(paths)?;
+            Self::write_from_master(paths, mode, vars, recovery)?;
         }
         Self::load(paths)
     }
@@ -23,43 +31,130 @@ impl GlobalCatalogue {
     }
 
     pub fn save(paths: &MmsPaths, catalogue: &McpCatalogue) -> Result<(), AppError> {
-        let serialised = serde_json::to_string_pretty(catalogue)?;
-        fs::write(paths.global_catalogue_path(), format!("{serialised}\n"))?;
-        Ok(())
+        write_catalogue(&paths.global_catalogue_path(), catalogue)
     }
 
-    pub fn write_from_master(paths: &MmsPaths) -> Result<(), AppError> {
-        let mut master: McpCatalogue = serde_json::from_str(MasterCatalogue::EMBEDDED_JSON)?;
-        for server in master.mcp_servers.values_mut() {
-            apply_env_substitutions(server);
+    pub fn write_from_master(
+        paths: &MmsPaths,
+        mode: PlaceholderMode,
+        vars: &dyn VariableSource,
+        recovery: Recovery,
+    ) -> Result<(), AppError> {
+        let master = MasterCatalogue::load(paths, recovery)?;
+        Self::save_base(paths, &master)?;
+        let mut global = master;
+        if mode == PlaceholderMode::Eager {
+            // Provider placeholders from the master stay as written until `sync` resolves them.
+            placeholder::expand_catalogue(&mut global, &VariablesOnly(vars))?;
         }
-        let serialised = serde_json::to_string_pretty(&master)?;
-        fs::write(paths.global_catalogue_path(), format!("{serialised}\n"))?;
-        Ok(())
+        write_catalogue(&paths.global_catalogue_path(), &global)
     }
 
-    fn read_from_path(path: PathBuf) -> Result<McpCatalogue, AppError> {
+    /// The master snapshot the global catalogue was generated from, if one was recorded.
+    pub fn load_base(paths: &MmsPaths) -> Result<Option<McpCatalogue>, AppError> {
+        let path = paths.global_base_path();
         if !path.exists() {
-            return Err(AppError::missing_file(path));
+            return Ok(None);
         }
-        let contents = fs::read_to_string(path)?;
-        Ok(serde_json::from_str(&contents)?)
+        read_catalogue(&path).map(Some)
+    }
+
+    fn save_base(paths: &MmsPaths, master: &McpCatalogue) -> Result<(), AppError> {
+        paths.ensure_config_dir()?;
+        write_catalogue(&paths.global_base_path(), master)
+    }
+
+    /// Merge the current master into the user's global catalogue.
+    ///
+    /// In eager mode placeholders are expanded on both master sides first, so values resolved
+    /// into `~/.mcp.json` do not count as user edits.
+    pub fn plan_upgrade(
+        paths: &MmsPaths,
+        mode: PlaceholderMode,
+        vars: &dyn VariableSource,
+        recovery: Recovery,
+    ) -> Result<(MergeOutcome, McpCatalogue), AppError> {
+        let master = MasterCatalogue::load(paths, recovery)?;
+        let base = Self::load_base(paths)?;
+        let mine = Self::load(paths)?;
+
+        let eager = mode == PlaceholderMode::Eager;
+        let theirs = effective(&master, vars, eager);
+        let mut outcome =
+            merge::three_way(base.map(|b| effective(&b, vars, eager)).as_ref(), &mine, &theirs)?;
+
+        let substituted: Vec<String> = outcome
+            .merged
+            .mcp_servers
+            .keys()
+            .filter(|name| {
+                master.mcp_servers.get(*name).zip(theirs.mcp_servers.get(*name)).is_some_and(
+                    |(template, expanded)| {
+                        serde_json::to_value(template).ok() != serde_json::to_value(expanded).ok()
+                    },
+                )
+            })
+            .cloned()
+            .collect();
+        for name in substituted {
+            outcome.merged.mark_resolved(&name, true);
+        }
+        Ok((outcome, master))
+    }
+
+    /// Write an upgraded global catalogue and remember the master it now reflects.
+    pub fn finish_upgrade(
+        paths: &MmsPaths,
+        merged: &McpCatalogue,
+        master: &McpCatalogue,
+    ) -> Result<(), AppError> {
+        let mut merged = merged.clone();
+        merged.meta.master_hash = master.meta.master_hash.clone();
+        Self::save(paths, &merged)?;
+        Self::save_base(paths, master)
+    }
+
+    /// Entries of `global` that are still exactly as generated from the master.
+    pub fn unmodified_entries(
+        paths: &MmsPaths,
+        global: &McpCatalogue,
+        mode: PlaceholderMode,
+        vars: &dyn VariableSource,
+    ) -> Result<BTreeSet<String>, AppError> {
+        let Some(base) = Self::load_base(paths)? else {
+            return Ok(BTreeSet::new());
+        };
+        let eager = mode == PlaceholderMode::Eager;
+        let base = effective(&base, vars, eager);
+        let same = |a: &McpServer, b: &McpServer| {
+            serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+        };
+        Ok(global
+            .mcp_servers
+            .iter()
+            .filter(|(name, server)| base.mcp_servers.get(*name).is_some_and(|b| same(b, server)))
+            .map(|(name, _)| name.clone())
+            .collect())
     }
-}
 
-fn apply_env_substitutions(server: &mut McpServer) {
-    for value in server.env.values_mut() {
-        if let Some(replacement) = expand_env_placeholder(value) {
-            *value = replacement;
+    fn read_from_path(path: PathBuf) -> Result<McpCatalogue, AppError> {
+        if !path.exists() {
+            return Err(AppError::missing_file(path));
         }
+        read_catalogue(&path)
     }
 }
 
-fn expand_env_placeholder(value: &str) -> Option<String> {
-    if value.starts_with("${") && value.ends_with('}') && value.len() > 3 {
-        let key = &value[2..value.len() - 1];
-        std::env::var(key).ok()
-    } else {
-        None
+/// `catalogue` as it reads in `~/.mcp.json`: with placeholders expanded in eager mode.
+fn effective(catalogue: &McpCatalogue, vars: &dyn VariableSource, eager: bool) -> McpCatalogue {
+    let mut expanded = catalogue.clone();
+    if eager {
+        for (name, server) in expanded.mcp_servers.iter_mut() {
+            let mut candidate = server.clone();
+            if placeholder::expand_server(name, &mut candidate, &VariablesOnly(vars)).is_ok() {
+                *server = candidate;
+            }
+        }
     }
+    expanded
 }
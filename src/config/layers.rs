@@ -0,0 +1,162 @@
+use super::global::GlobalCatalogue;
+use super::local::LocalCatalogue;
+use super::master::Recovery;
+use super::migrate::parse_catalogue;
+use super::model::McpCatalogue;
+use super::paths::MmsPaths;
+use super::placeholder::VariableSource;
+use super::settings::MmsSettings;
+use super::signature;
+use crate::error::AppError;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A shared catalogue configured under `[[teams]]` in `config.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamLayer {
+    pub name: String,
+    /// A catalogue file, or a directory such as a git checkout holding `.mcp.json`.
+    pub path: PathBuf,
+    /// Repository the checkout was cloned from by `mms catalogue subscribe`.
+    pub url: Option<String>,
+    /// Branch, tag or commit the subscription follows.
+    pub git_ref: Option<String>,
+    /// Accept the catalogue, with a warning on every load, when its signature does not verify.
+    pub allow_unsigned: bool,
+}
+
+impl TeamLayer {
+    pub fn catalogue_path(&self) -> PathBuf {
+        if self.path.is_dir() {
+            self.path.join(".mcp.json")
+        } else {
+            self.path.clone()
+        }
+    }
+
+    /// Read the catalogue after checking the signature next to it.
+    pub fn load(&self, paths: &MmsPaths) -> Result<McpCatalogue, AppError> {
+        let path = self.catalogue_path();
+        if !path.exists() {
+            return Err(AppError::missing_file(path));
+        }
+        let contents = fs::read_to_string(&path)?;
+        signature::check_stored(paths, &path, contents.as_bytes(), self.allow_unsigned)?;
+        Self::parse(&path, &contents)
+    }
+
+    /// Parse catalogue contents already read, and verified, from `path`.
+    pub fn parse(path: &Path, contents: &str) -> Result<McpCatalogue, AppError> {
+        // Team catalogues belong to someone else; never rewrite them in place.
+        let (catalogue, _) = parse_catalogue(contents)?;
+        if catalogue.has_references() {
+            return Err(AppError::config(format!(
+                "{} uses `extends`, which only project catalogues may use",
+                path.display()
+            )));
+        }
+        Ok(catalogue)
+    }
+}
+
+/// The layer a resolved server was taken from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layer {
+    /// An entry of `~/.mcp.json` still as generated from the master catalogue.
+    Master,
+    Team(String),
+    /// An entry added or edited in `~/.mcp.json`.
+    User,
+    Project,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Master => write!(f, "master"),
+            Self::Team(name) => write!(f, "team:{name}"),
+            Self::User => write!(f, "user"),
+            Self::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// Servers resolved through master < team layers < `~/.mcp.json` < project.
+#[derive(Debug, Clone)]
+pub struct CatalogueStack {
+    /// The winning definition of every server, carrying `~/.mcp.json`'s metadata.
+    pub catalogue: McpCatalogue,
+    pub origins: BTreeMap<String, Layer>,
+}
+
+impl CatalogueStack {
+    /// Resolve the user-wide stack, creating `~/.mcp.json` from the master when needed.
+    pub fn load(
+        paths: &MmsPaths,
+        settings: &MmsSettings,
+        vars: &dyn VariableSource,
+        recovery: Recovery,
+    ) -> Result<Self, AppError> {
+        let global = GlobalCatalogue::ensure(paths, settings.placeholders, vars, recovery)?;
+        Self::over(global, paths, settings, vars)
+    }
+
+    /// Layer the configured team catalogues under an already loaded `~/.mcp.json`.
+    ///
+    /// Team entries replace master entries but never ones the user added or edited.
+    pub fn over(
+        global: McpCatalogue,
+        paths: &MmsPaths,
+        settings: &MmsSettings,
+        vars: &dyn VariableSource,
+    ) -> Result<Self, AppError> {
+        let unmodified =
+            GlobalCatalogue::unmodified_entries(paths, &global, settings.placeholders, vars)?;
+        let mut origins: BTreeMap<String, Layer> = global
+            .mcp_servers
+            .keys()
+            .map(|name| {
+                let layer = if unmodified.contains(name) { Layer::Master } else { Layer::User };
+                (name.clone(), layer)
+            })
+            .collect();
+        let mut catalogue = global;
+
+        for team in &settings.teams {
+            let layer = match team.load(paths) {
+                Ok(layer) => layer,
+                Err(err) => {
+                    eprintln!("⚠️  Skipping team catalogue '{}': {err}", team.name);
+                    continue;
+                }
+            };
+            for (name, server) in layer.mcp_servers {
+                if origins.get(&name) == Some(&Layer::User) {
+                    continue;
+                }
+                catalogue.mcp_servers.insert(name.clone(), server);
+                catalogue.mark_resolved(&name, false);
+                origins.insert(name, Layer::Team(team.name.clone()));
+            }
+        }
+        Ok(Self { catalogue, origins })
+    }
+
+    /// Put the project catalogue on top, expanding its `extends` references against the stack.
+    pub fn with_project(mut self, local: &McpCatalogue) -> Result<Self, AppError> {
+        let resolved = LocalCatalogue::resolve(local, &self.catalogue)?;
+        for (name, server) in resolved.mcp_servers {
+            let secret = resolved.meta.resolved_servers.contains(&name);
+            self.catalogue.mcp_servers.insert(name.clone(), server);
+            self.catalogue.mark_resolved(&name, secret);
+            self.origins.insert(name, Layer::Project);
+        }
+        Ok(self)
+    }
+
+    pub fn origin(&self, name: &str) -> Option<&Layer> {
+        self.origins.get(name)
+    }
+}
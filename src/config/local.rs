@@ -1,3 +1,4 @@
+use super::migrate::{read_catalogue, write_catalogue};
 use super::model::McpCatalogue;
 use crate::error::AppError;
 use std::fs;
@@ -17,8 +18,7 @@ impl LocalCatalogue {
         }
         let mut catalogue = McpCatalogue::empty();
         catalogue.extras = serde_json::Map::new();
-        let serialised = serde_json::to_string_pretty(&catalogue)?;
-        fs::write(&path, format!("{serialised}\n"))?;
+        write_catalogue(&path, &catalogue)?;
         Ok(path)
     }
 
@@ -30,8 +30,12 @@ impl LocalCatalogue {
                 path.display()
             )));
         }
-        let serialised = serde_json::to_string_pretty(global)?;
-        fs::write(&path, format!("{serialised}\n"))?;
+        let mut local = global.clone();
+        local.meta.master_hash = None;
+        for (name, server) in &global.mcp_servers {
+            local.mark_copied(name, Some(server));
+        }
+        write_catalogue(&path, &local)?;
         Ok(path)
     }
 
@@ -47,18 +51,47 @@ impl LocalCatalogue {
 
     pub fn load(start_dir: &Path, global_path: &Path) -> Result<(McpCatalogue, PathBuf), AppError> {
         if let Some(path) = Self::discover(start_dir, global_path) {
-            let contents = fs::read_to_string(&path)?;
-            let catalogue: McpCatalogue = serde_json::from_str(&contents)?;
+            let catalogue = read_catalogue(&path)?;
             Ok((catalogue, path))
         } else {
             Err(AppError::config("No .mcp.json found in current directory or any parent"))
         }
     }
 
+    /// Expand `extends` references against the global catalogue, applying local overrides.
+    pub fn resolve(
+        catalogue: &McpCatalogue,
+        global: &McpCatalogue,
+    ) -> Result<McpCatalogue, AppError> {
+        let mut resolved = catalogue.clone();
+        let mut inherited_secrets = Vec::new();
+        for (name, server) in resolved.mcp_servers.iter_mut() {
+            let Some(base_name) = &server.extends else {
+                continue;
+            };
+            let base = global.mcp_servers.get(base_name).ok_or_else(|| {
+                AppError::config(format!(
+                    "MCP server '{name}' extends '{base_name}', which is not in the global catalogue"
+                ))
+            })?;
+            if base.extends.is_some() {
+                return Err(AppError::config(format!(
+                    "MCP server '{name}' extends '{base_name}', which is itself a reference"
+                )));
+            }
+            if global.meta.resolved_servers.contains(base_name) {
+                inherited_secrets.push(name.clone());
+            }
+            *server = server.merged_over(base);
+        }
+        for name in inherited_secrets {
+            resolved.mark_resolved(&name, true);
+        }
+        Ok(resolved)
+    }
+
     pub fn save(path: &Path, catalogue: &McpCatalogue) -> Result<(), AppError> {
-        let serialised = serde_json::to_string_pretty(catalogue)?;
-        fs::write(path, format!("{serialised}\n"))?;
-        Ok(())
+        write_catalogue(path, catalogue)
     }
 
     pub fn remove_file(path: &Path) -> Result<bool, AppError> {
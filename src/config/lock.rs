@@ -0,0 +1,576 @@
+use super::model::{McpCatalogue, McpServer};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Lockfile written next to the project `.mcp.json`.
+pub const LOCK_FILE: &str = ".mcp.lock";
+
+const LOCK_VERSION: u32 = 1;
+
+/// The package a server launches, as written in its `args`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSpec {
+    /// `npx` package such as `@scope/name@latest`.
+    Npm { name: String, requested: Option<String> },
+    /// `uvx` package such as `mcp-server-fetch` or `name[extra]>=1`.
+    PyPi { name: String, requested: Option<String> },
+    /// `uvx --from git+https://host/repo[@ref]`.
+    Git { url: String, git_ref: Option<String> },
+}
+
+impl PackageSpec {
+    /// Locate the package argument of an `npx` or `uvx` server: its index and parsed spec.
+    pub fn locate(server: &McpServer) -> Option<(usize, Self)> {
+        let command = server.command.as_deref()?;
+        let program = Path::new(command).file_stem()?.to_str()?;
+        let (takes_value, explicit): (&[&str], &[&str]) = match program {
+            "npx" => (&["--package", "-p", "--call", "-c"], &["--package", "-p"]),
+            "uvx" => {
+                (&["--from", "--with", "--python", "-p", "--index", "--index-url"], &["--from"])
+            }
+            _ => return None,
+        };
+        let mut index = 0;
+        let mut found = None;
+        while index < server.args.len() {
+            let arg = &server.args[index];
+            if explicit.contains(&arg.as_str()) && index + 1 < server.args.len() {
+                found = Some(index + 1);
+                break;
+            }
+            if takes_value.contains(&arg.as_str()) {
+                index += 2;
+                continue;
+            }
+            if !arg.starts_with('-') {
+                found = Some(index);
+                break;
+            }
+            index += 1;
+        }
+        let index = found?;
+        let arg = &server.args[index];
+        let spec = if program == "npx" { Self::parse_npm(arg) } else { Self::parse_uv(arg) };
+        spec.map(|spec| (index, spec))
+    }
+
+    fn parse_npm(arg: &str) -> Option<Self> {
+        // A leading `@` belongs to the scope, not the version.
+        let split = arg.get(1..)?.find('@').map(|at| at + 1);
+        let (name, requested) = match split {
+            Some(at) => (&arg[..at], Some(arg[at + 1..].to_string())),
+            None => (arg, None),
+        };
+        (!name.is_empty() && !name.contains(':'))
+            .then(|| Self::Npm { name: name.to_string(), requested })
+    }
+
+    fn parse_uv(arg: &str) -> Option<Self> {
+        if let Some(rest) = arg.strip_prefix("git+") {
+            if !is_remote_url(rest) {
+                return None;
+            }
+            let host_end = match rest.strip_prefix("git@") {
+                Some(scp) => scp.find(':')? + 5,
+                None => rest.find("://")? + 3,
+            };
+            let path_start = rest[host_end..].find('/').map_or(rest.len(), |at| host_end + at);
+            return Some(match rest[path_start..].rfind('@') {
+                Some(at) => Self::Git {
+                    url: rest[..path_start + at].to_string(),
+                    git_ref: Some(rest[path_start + at + 1..].to_string()),
+                },
+                None => Self::Git { url: rest.to_string(), git_ref: None },
+            });
+        }
+        if arg.contains("://") || arg.contains(['/', '\\']) {
+            return None;
+        }
+        let split = arg.find(['<', '>', '=', '!', '~']).unwrap_or(arg.len());
+        let (name, requested) = arg.split_at(split);
+        (!name.is_empty()).then(|| Self::PyPi {
+            name: name.to_string(),
+            requested: Some(requested.to_string()).filter(|r| !r.is_empty()),
+        })
+    }
+
+    /// Whether the spec already names one exact version or commit.
+    pub fn is_exact(&self) -> bool {
+        match self {
+            Self::Npm { requested, .. } => requested.as_deref().is_some_and(is_exact_version),
+            Self::PyPi { requested, .. } => requested
+                .as_deref()
+                .and_then(|r| r.strip_prefix("=="))
+                .is_some_and(|version| !version.contains(['*', ','])),
+            Self::Git { git_ref, .. } => git_ref.as_deref().is_some_and(is_commit),
+        }
+    }
+
+    /// The argument that launches exactly `version` (a version number or commit SHA).
+    pub fn pinned(&self, version: &str) -> String {
+        match self {
+            Self::Npm { name, .. } => format!("{name}@{version}"),
+            Self::PyPi { name, .. } => format!("{name}=={version}"),
+            Self::Git { url, .. } => format!("git+{url}@{version}"),
+        }
+    }
+
+    /// Look up the exact version or commit the spec currently points at.
+    pub fn resolve(&self, resolver: &dyn Resolver) -> Result<String, AppError> {
+        let version = match self {
+            Self::Npm { name, requested } => resolver.npm(name, requested.as_deref())?,
+            Self::PyPi { name, requested } => {
+                let base = name.split('[').next().unwrap_or(name);
+                resolver.pypi(base, requested.as_deref())?
+            }
+            Self::Git { url, git_ref } => resolver.git(url, git_ref.as_deref())?,
+        };
+        if !self.accepts(&version) {
+            return Err(AppError::config(format!(
+                "resolver returned an unusable version {version:?}"
+            )));
+        }
+        Ok(version)
+    }
+
+    /// Whether `version` is something [`Self::pinned`] may put on a command line for this spec.
+    pub fn accepts(&self, version: &str) -> bool {
+        match self {
+            Self::Npm { .. } => is_exact_version(version),
+            Self::PyPi { .. } => is_pypi_version(version),
+            Self::Git { .. } => is_commit(version),
+        }
+    }
+}
+
+/// A semver version such as `1.2.3` or `1.2.3-beta.1+build`.
+fn is_exact_version(version: &str) -> bool {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || ".-+".contains(c))
+}
+
+/// A PEP 440 release such as `0.3.1`, `1.0rc1` or `2!1.0+local`.
+fn is_pypi_version(version: &str) -> bool {
+    version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || ".!+-_".contains(c))
+}
+
+/// Only network remotes are resolved; anything else could smuggle options into `git`.
+fn is_remote_url(url: &str) -> bool {
+    ["https://", "ssh://", "git@"].iter().any(|prefix| url.starts_with(prefix))
+}
+
+fn is_commit(git_ref: &str) -> bool {
+    git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Turns floating package specs into exact versions or commits.
+pub trait Resolver {
+    /// Exact version of an npm package for a tag or range (`latest` when `None`).
+    fn npm(&self, name: &str, requested: Option<&str>) -> Result<String, AppError>;
+    /// Exact version of a PyPI package; only the latest release is supported.
+    fn pypi(&self, name: &str, requested: Option<&str>) -> Result<String, AppError>;
+    /// Commit a git ref points at (`HEAD` when `None`).
+    fn git(&self, url: &str, git_ref: Option<&str>) -> Result<String, AppError>;
+}
+
+/// Asks `npm view`, the PyPI JSON API and `git ls-remote`.
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn npm(&self, name: &str, requested: Option<&str>) -> Result<String, AppError> {
+        let spec = format!("{name}@{}", requested.unwrap_or("latest"));
+        let output = run("npm", &["view", &spec, "version", "--json"])?;
+        // Ranges matching several versions print an array in ascending order.
+        match serde_json::from_str::<Value>(&output) {
+            Ok(Value::String(version)) => Ok(version),
+            Ok(Value::Array(versions)) => versions
+                .last()
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| AppError::config(format!("npm knows no version of {spec}"))),
+            _ => Err(AppError::config(format!("npm knows no version of {spec}"))),
+        }
+    }
+
+    fn pypi(&self, name: &str, requested: Option<&str>) -> Result<String, AppError> {
+        if let Some(requested) = requested {
+            return Err(AppError::config(format!(
+                "cannot resolve the PyPI constraint '{name}{requested}'; pin it by hand"
+            )));
+        }
+        let url = format!("https://pypi.org/pypi/{name}/json");
+        let response = ureq::get(&url)
+            .timeout(Duration::from_secs(30))
+            .call()
+            .map_err(|err| AppError::config(format!("could not fetch {url}: {err}")))?;
+        let body = response
+            .into_string()
+            .map_err(|err| AppError::config(format!("could not read {url}: {err}")))?;
+        let doc: Value = serde_json::from_str(&body)?;
+        doc["info"]["version"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AppError::config(format!("{url} does not list a version")))
+    }
+
+    fn git(&self, url: &str, git_ref: Option<&str>) -> Result<String, AppError> {
+        if !is_remote_url(url) {
+            return Err(AppError::config(format!(
+                "'{url}' is not an https://, ssh:// or git@ repository URL"
+            )));
+        }
+        let git_ref = git_ref.unwrap_or("HEAD");
+        if is_commit(git_ref) {
+            return Ok(git_ref.to_string());
+        }
+        if git_ref == "HEAD" {
+            let output = run("git", &["ls-remote", "--", url, "HEAD"])?;
+            return ref_commit(&output, &["HEAD"])
+                .ok_or_else(|| AppError::config(format!("{url} has no HEAD")));
+        }
+        // Patterns match any ref ending in them, so ask for each full name and match exactly.
+        let (tag, peeled, head) = (
+            format!("refs/tags/{git_ref}"),
+            format!("refs/tags/{git_ref}^{{}}"),
+            format!("refs/heads/{git_ref}"),
+        );
+        let output = run("git", &["ls-remote", "--", url, &tag, &peeled, &head])?;
+        ref_commit(&output, &[&peeled, &tag, &head])
+            .ok_or_else(|| AppError::config(format!("{url} has no branch or tag '{git_ref}'")))
+    }
+}
+
+/// The commit of the first of `names` listed in `git ls-remote` output.
+///
+/// Annotated tags list the tag object under their name and the commit under `name^{}`.
+fn ref_commit(output: &str, names: &[&str]) -> Option<String> {
+    let refs: BTreeMap<&str, &str> = output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(sha, name)| (name.trim(), sha.trim()))
+        .collect();
+    names.iter().find_map(|name| refs.get(name)).map(|sha| sha.to_string())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| AppError::config(format!("could not run {program}: {err}")))?;
+    if !output.status.success() {
+        return Err(AppError::config(format!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The exact version recorded for one server's package argument.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The argument as written in `.mcp.json`, e.g. `@upstash/context7-mcp@latest`.
+    pub package: String,
+    /// Version number or commit SHA it resolved to.
+    pub version: String,
+}
+
+/// Contents of `.mcp.lock`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lockfile {
+    pub lock_version: u32,
+    #[serde(default)]
+    pub servers: BTreeMap<String, LockedPackage>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self { lock_version: LOCK_VERSION, servers: BTreeMap::new() }
+    }
+}
+
+/// How `lock` treated one server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockOutcome {
+    Pinned {
+        name: String,
+        arg: String,
+    },
+    Kept(String),
+    /// The server already names an exact version in `.mcp.json`.
+    Exact(String),
+    Dropped(String),
+    /// The package could not be resolved; any earlier pin is kept.
+    Failed {
+        name: String,
+        error: String,
+    },
+}
+
+impl Lockfile {
+    pub fn path(workspace: &Path) -> PathBuf {
+        workspace.join(LOCK_FILE)
+    }
+
+    pub fn load(workspace: &Path) -> Result<Option<Self>, AppError> {
+        let path = Self::path(workspace);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let lock: Self = serde_json::from_str(&fs::read_to_string(&path)?).map_err(|err| {
+            AppError::config(format!("{} is not a valid lockfile: {err}", path.display()))
+        })?;
+        if lock.lock_version > LOCK_VERSION {
+            return Err(AppError::config(format!(
+                "{} uses lockfile version {}, newer than this mms supports",
+                path.display(),
+                lock.lock_version
+            )));
+        }
+        let valid = |v: &str| is_exact_version(v) || is_pypi_version(v) || is_commit(v);
+        if let Some((name, locked)) = lock.servers.iter().find(|(_, l)| !valid(&l.version)) {
+            return Err(AppError::config(format!(
+                "{} pins '{name}' to {:?}, which is not a version number or commit SHA",
+                path.display(),
+                locked.version
+            )));
+        }
+        Ok(Some(lock))
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<(), AppError> {
+        let serialised = format!("{}\n", serde_json::to_string_pretty(self)?);
+        fs::write(Self::path(workspace), serialised)?;
+        Ok(())
+    }
+
+    /// Pin every floating package in `catalogue`, re-resolving the servers in `update`.
+    ///
+    /// Entries whose package argument is unchanged are kept; the rest are resolved again. A
+    /// server that fails to resolve is reported as [`LockOutcome::Failed`] and the others are
+    /// still pinned.
+    pub fn lock(
+        &mut self,
+        catalogue: &McpCatalogue,
+        update: &[String],
+        resolver: &dyn Resolver,
+    ) -> Result<Vec<LockOutcome>, AppError> {
+        let mut outcomes = Vec::new();
+        let mut servers = BTreeMap::new();
+        for (name, server) in &catalogue.mcp_servers {
+            let Some((index, spec)) = PackageSpec::locate(server) else {
+                continue;
+            };
+            if spec.is_exact() {
+                outcomes.push(LockOutcome::Exact(name.clone()));
+                continue;
+            }
+            let package = &server.args[index];
+            let existing = self.servers.get(name).filter(|locked| locked.package == *package);
+            let locked = match existing {
+                Some(locked) if !update.contains(name) => {
+                    outcomes.push(LockOutcome::Kept(name.clone()));
+                    locked.clone()
+                }
+                _ => match spec.resolve(resolver) {
+                    Ok(version) => {
+                        outcomes.push(LockOutcome::Pinned {
+                            name: name.clone(),
+                            arg: spec.pinned(&version),
+                        });
+                        LockedPackage { package: package.clone(), version }
+                    }
+                    Err(err) => {
+                        outcomes.push(LockOutcome::Failed {
+                            name: name.clone(),
+                            error: format!("{package}: {err}"),
+                        });
+                        match self.servers.get(name) {
+                            Some(previous) => previous.clone(),
+                            None => continue,
+                        }
+                    }
+                },
+            };
+            servers.insert(name.clone(), locked);
+        }
+        for name in self.servers.keys().filter(|name| !servers.contains_key(*name)) {
+            outcomes.push(LockOutcome::Dropped(name.clone()));
+        }
+        self.servers = servers;
+        Ok(outcomes)
+    }
+
+    /// Replace pinned package arguments in `catalogue`, returning warnings for stale entries.
+    pub fn apply(&self, catalogue: &mut McpCatalogue) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (name, locked) in &self.servers {
+            let Some(server) = catalogue.mcp_servers.get_mut(name) else {
+                continue;
+            };
+            match PackageSpec::locate(server) {
+                Some((index, spec)) if server.args[index] == locked.package => {
+                    if spec.accepts(&locked.version) {
+                        server.args[index] = spec.pinned(&locked.version);
+                    } else {
+                        warnings.push(format!(
+                            "{LOCK_FILE} pins '{name}' to {:?}, which does not fit its package; \
+                             run `mms lock --update {name}`",
+                            locked.version
+                        ));
+                    }
+                }
+                _ => warnings.push(format!(
+                    "{LOCK_FILE} entry for '{name}' no longer matches its package; \
+                     run `mms lock` to re-pin it"
+                )),
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(command: &str, args: &[&str]) -> McpServer {
+        McpServer {
+            command: Some(command.to_string()),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ..McpServer::default()
+        }
+    }
+
+    #[test]
+    fn locates_package_specs() {
+        let (index, spec) =
+            PackageSpec::locate(&server("npx", &["-y", "@upstash/context7-mcp@latest"])).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(
+            spec,
+            PackageSpec::Npm {
+                name: "@upstash/context7-mcp".into(),
+                requested: Some("latest".into())
+            }
+        );
+        assert_eq!(spec.pinned("1.0.6"), "@upstash/context7-mcp@1.0.6");
+        assert!(!spec.is_exact());
+
+        let serena = server(
+            "uvx",
+            &["--from", "git+https://github.com/oraios/serena", "serena", "start-mcp-server"],
+        );
+        let (index, spec) = PackageSpec::locate(&serena).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(
+            spec,
+            PackageSpec::Git { url: "https://github.com/oraios/serena".into(), git_ref: None }
+        );
+        assert_eq!(
+            spec.pinned(&"a".repeat(40)),
+            format!("git+https://github.com/oraios/serena@{}", "a".repeat(40))
+        );
+
+        let hostile = server("uvx", &["--from", "git+--upload-pack=touch x;://x/y@/tmp", "y"]);
+        assert!(PackageSpec::locate(&hostile).is_none());
+        assert!(SystemResolver.git("--upload-pack=touch x", None).is_err());
+        let (_, spec) = PackageSpec::locate(&server(
+            "uvx",
+            &["--from", "git+git@github.com:acme/tool@v1", "t"],
+        ))
+        .unwrap();
+        assert_eq!(
+            spec,
+            PackageSpec::Git { url: "git@github.com:acme/tool".into(), git_ref: Some("v1".into()) }
+        );
+
+        let (_, spec) = PackageSpec::locate(&server("uvx", &["mcp-server-fetch==0.3.1"])).unwrap();
+        assert!(spec.is_exact());
+        assert!(PackageSpec::locate(&server("docker", &["run", "acme/db"])).is_none());
+    }
+
+    #[test]
+    fn lockfiles_only_hold_versions_and_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |version: &str| {
+            let lock = serde_json::json!({ "lockVersion": 1, "servers": {
+                "a": { "package": "pkg@latest", "version": version } } });
+            fs::write(Lockfile::path(dir.path()), lock.to_string()).unwrap();
+        };
+        for good in ["1.2.3", "1.2.3-beta.1", "0.3.1", "2!1.0+local"] {
+            write(good);
+            assert!(Lockfile::load(dir.path()).is_ok(), "{good}");
+        }
+        for bad in ["npm:evil@1.0.0", "1.0.0 --foo", "", "../x"] {
+            write(bad);
+            assert!(Lockfile::load(dir.path()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn ls_remote_refs_match_exactly_and_prefer_peeled_tags() {
+        let output = "1111111111111111111111111111111111111111\trefs/heads/feature/main\n\
+                      2222222222222222222222222222222222222222\trefs/heads/main\n\
+                      3333333333333333333333333333333333333333\trefs/tags/v1\n\
+                      4444444444444444444444444444444444444444\trefs/tags/v1^{}\n";
+        let main = ref_commit(output, &["refs/tags/main^{}", "refs/tags/main", "refs/heads/main"]);
+        assert_eq!(main.as_deref(), Some("2222222222222222222222222222222222222222"));
+        let v1 = ref_commit(output, &["refs/tags/v1^{}", "refs/tags/v1", "refs/heads/v1"]);
+        assert_eq!(v1.as_deref(), Some("4444444444444444444444444444444444444444"));
+    }
+
+    /// Resolves every package to the same version.
+    struct Fixed(&'static str);
+
+    impl Resolver for Fixed {
+        fn npm(&self, _: &str, _: Option<&str>) -> Result<String, AppError> {
+            Ok(self.0.to_string())
+        }
+
+        fn pypi(&self, _: &str, _: Option<&str>) -> Result<String, AppError> {
+            Ok(self.0.to_string())
+        }
+
+        fn git(&self, _: &str, _: Option<&str>) -> Result<String, AppError> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn lock_keeps_unchanged_entries_and_applies_pins() {
+        let resolver = Fixed("1.2.3");
+        let mut catalogue = McpCatalogue::empty();
+        catalogue.mcp_servers.insert("a".into(), server("npx", &["-y", "pkg@latest"]));
+        let mut lock = Lockfile::default();
+        let outcomes = lock.lock(&catalogue, &[], &resolver).unwrap();
+        assert_eq!(outcomes, [LockOutcome::Pinned { name: "a".into(), arg: "pkg@1.2.3".into() }]);
+        assert_eq!(lock.lock(&catalogue, &[], &resolver).unwrap(), [LockOutcome::Kept("a".into())]);
+
+        let mut synced = catalogue.clone();
+        assert!(lock.apply(&mut synced).is_empty());
+        assert_eq!(synced.mcp_servers["a"].args, ["-y", "pkg@1.2.3"]);
+
+        lock.servers.get_mut("a").unwrap().version =
+            "0123456789abcdef0123456789abcdef01234567".into();
+        let mut synced = catalogue.clone();
+        assert_eq!(lock.apply(&mut synced).len(), 1);
+        assert_eq!(synced.mcp_servers["a"].args[1], "pkg@latest");
+
+        catalogue.mcp_servers.get_mut("a").unwrap().args[1] = "other@latest".into();
+        assert_eq!(lock.apply(&mut catalogue).len(), 1);
+        assert_eq!(catalogue.mcp_servers["a"].args[1], "other@latest");
+    }
+}
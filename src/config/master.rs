@@ -1,43 +1,336 @@
-use super::model::McpCatalogue;
+use super::migrate::{self, parse_catalogue};
+use super::model::{McpCatalogue, McpServer, CURRENT_SCHEMA_VERSION};
 use super::paths::MmsPaths;
+use super::signature::{self, SIGNATURE_SUFFIX};
+use super::validate::{self, Severity};
 use crate::error::AppError;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Embedded authoritative catalogue shipped with the CLI.
 pub struct MasterCatalogue;
 
 impl MasterCatalogue {
     pub const EMBEDDED_JSON: &'static str = include_str!("master_data.json");
+    /// SHA-256 of [`Self::EMBEDDED_JSON`], computed by the build script.
+    pub const EMBEDDED_SHA256: &'static str = env!("MMS_MASTER_SHA256");
+    /// Version of the mms build the embedded catalogue shipped with.
+    pub const EMBEDDED_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    /// The embedded catalogue, stamped with its hash.
+    pub fn embedded() -> Result<McpCatalogue, AppError> {
+        let (mut catalogue, _) = parse_catalogue(Self::EMBEDDED_JSON)?;
+        catalogue.meta.master_hash = Some(Self::EMBEDDED_SHA256.to_string());
+        Ok(catalogue)
+    }
 
     /// Ensure the master catalogue exists in the CLI config directory and return it.
-    pub fn load(paths: &MmsPaths) -> Result<McpCatalogue, AppError> {
+    ///
+    /// A file that is not valid catalogue JSON is moved to quarantine, never silently
+    /// overwritten. One written for a newer schema is an error and stays where it is.
+    pub fn load(paths: &MmsPaths, recovery: Recovery) -> Result<McpCatalogue, AppError> {
         paths.ensure_config_dir()?;
+        if let Some(quarantined) = Self::quarantined(paths)? {
+            return Self::recover(paths, recovery, &quarantined);
+        }
         let master_path = paths.master_catalogue_path();
         if !master_path.exists() {
             Self::write_embedded(paths)?;
         }
         let contents = fs::read_to_string(&master_path)?;
-        match serde_json::from_str(&contents) {
-            Ok(catalogue) => Ok(catalogue),
-            Err(_) => {
-                // File might be corrupt, attempt to recover by overwriting.
-                Self::write_embedded(paths)?;
-                let new_contents = fs::read_to_string(&master_path)?;
-                serde_json::from_str(&new_contents).map_err(Into::into)
+        match Self::parse_installed(&contents) {
+            Ok(catalogue) => {
+                Self::verify_installed(paths, &contents, &catalogue)?;
+                Ok(catalogue)
             }
+            Err(err) if newer_schema(&contents) => Err(err),
+            Err(err) => {
+                eprintln!("❌ {}", describe_error(&master_path, &contents, &err));
+                let quarantined = Self::quarantine(paths)?;
+                eprintln!("   Moved the unreadable master catalogue to {}", quarantined.display());
+                Self::recover(paths, recovery, &quarantined)
+            }
+        }
+    }
+
+    /// Whether loading would quarantine the stored master, or already has.
+    pub fn needs_recovery(paths: &MmsPaths) -> bool {
+        if paths.quarantine_marker_path().exists() {
+            return true;
+        }
+        match fs::read_to_string(paths.master_catalogue_path()) {
+            Ok(contents) => Self::parse_installed(&contents).is_err() && !newer_schema(&contents),
+            Err(_) => false,
         }
     }
 
+    /// The stored master catalogue if it exists and parses, without writing or prompting.
+    pub fn read_installed(paths: &MmsPaths) -> Option<McpCatalogue> {
+        let contents = fs::read_to_string(paths.master_catalogue_path()).ok()?;
+        Self::parse_installed(&contents).ok()
+    }
+
+    /// Parse the stored master, stamping it with the hash of the published bytes it holds.
+    fn parse_installed(contents: &str) -> Result<McpCatalogue, AppError> {
+        let (mut catalogue, _) = parse_catalogue(contents)?;
+        // Masters written by older versions recorded their hash; newer ones are stored verbatim.
+        if catalogue.meta.master_hash.is_none() {
+            catalogue.meta.master_hash = Some(format!("{:x}", Sha256::digest(contents.as_bytes())));
+        }
+        Ok(catalogue)
+    }
+
+    /// Check the stored master on every load: the embedded servers, a copy an earlier mms
+    /// installed from its own embedded catalogue, or a good signature.
+    fn verify_installed(
+        paths: &MmsPaths,
+        contents: &str,
+        catalogue: &McpCatalogue,
+    ) -> Result<(), AppError> {
+        let servers = |catalogue: &McpCatalogue| serde_json::to_value(&catalogue.mcp_servers).ok();
+        if servers(catalogue) == servers(&Self::embedded()?) {
+            return Ok(());
+        }
+        let hash = format!("{:x}", Sha256::digest(contents.as_bytes()));
+        let embedded = fs::read_to_string(paths.embedded_master_marker_path()).unwrap_or_default();
+        if embedded.trim() == hash {
+            eprintln!(
+                "ℹ️  The master catalogue came with an earlier mms; run `mms master reset` to \
+                 install the one embedded in {}",
+                Self::EMBEDDED_VERSION
+            );
+            return Ok(());
+        }
+        let allow_unsigned = paths.unsigned_master_marker_path().exists();
+        signature::check_stored(
+            paths,
+            &paths.master_catalogue_path(),
+            contents.as_bytes(),
+            allow_unsigned,
+        )
+        .map_err(|err| match err {
+            AppError::Config(message) => AppError::Config(format!(
+                "{message}\nIf an earlier mms installed this master itself, run `mms master reset` \
+                 to replace it with the catalogue embedded in this build"
+            )),
+            err => err,
+        })
+    }
+
+    /// Path of the quarantined master awaiting a fix, if any.
+    pub fn quarantined(paths: &MmsPaths) -> Result<Option<PathBuf>, AppError> {
+        let marker = paths.quarantine_marker_path();
+        if !marker.exists() {
+            return Ok(None);
+        }
+        Ok(Some(PathBuf::from(fs::read_to_string(marker)?.trim())))
+    }
+
+    fn quarantine(paths: &MmsPaths) -> Result<PathBuf, AppError> {
+        let dir = paths.quarantine_dir();
+        fs::create_dir_all(&dir)?;
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let target = dir.join(format!("master-{stamp}.json"));
+        fs::rename(paths.master_catalogue_path(), &target)?;
+        fs::write(paths.quarantine_marker_path(), format!("{}\n", target.display()))?;
+        Ok(target)
+    }
+
+    fn recover(
+        paths: &MmsPaths,
+        recovery: Recovery,
+        quarantined: &Path,
+    ) -> Result<McpCatalogue, AppError> {
+        if recovery == Recovery::Refuse {
+            return Err(AppError::config(format!(
+                "the master catalogue is quarantined at {path}; fix it and run \
+                 `mms master update --from {path}`, or restore the embedded copy with \
+                 `mms master reset` or `--repair`",
+                path = quarantined.display()
+            )));
+        }
+        Self::write_embedded(paths)?;
+        eprintln!("✅ Restored the embedded master catalogue");
+        Self::embedded()
+    }
+
+    /// Read a candidate catalogue from a file path or an `http(s)://` URL.
+    pub fn fetch(source: &str) -> Result<String, AppError> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let response = ureq::get(source)
+                .timeout(Duration::from_secs(30))
+                .call()
+                .map_err(|err| AppError::config(format!("could not fetch {source}: {err}")))?;
+            return response
+                .into_string()
+                .map_err(|err| AppError::config(format!("could not read {source}: {err}")));
+        }
+        let path = Path::new(source);
+        if !path.exists() {
+            return Err(AppError::missing_file(path));
+        }
+        Ok(fs::read_to_string(path)?)
+    }
+
+    /// Check a candidate catalogue and parse it, refusing anything with validation errors.
+    pub fn parse_candidate(
+        source: &str,
+        contents: &str,
+        sha256: Option<&str>,
+    ) -> Result<McpCatalogue, AppError> {
+        let actual = format!("{:x}", Sha256::digest(contents.as_bytes()));
+        if let Some(expected) = sha256 {
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                return Err(AppError::config(format!(
+                    "SHA-256 mismatch for {source}: expected {expected}, got {actual}"
+                )));
+            }
+        }
+
+        let diagnostics = validate::validate_str(Path::new(source), contents);
+        for diagnostic in &diagnostics {
+            eprintln!("{diagnostic}");
+        }
+        if validate::has_errors(&diagnostics) {
+            return Err(AppError::config(format!("{source} failed validation")));
+        }
+        let (mut catalogue, _) = parse_catalogue(contents)?;
+        catalogue.meta.master_hash = Some(actual);
+        if catalogue.has_references() {
+            return Err(AppError::config(format!(
+                "{source} uses `extends`, which only project catalogues may use"
+            )));
+        }
+        if catalogue.mcp_servers.is_empty() {
+            return Err(AppError::config(format!("{source} defines no MCP servers")));
+        }
+        Ok(catalogue)
+    }
+
+    /// Replace the stored master catalogue, quarantining an unreadable one first.
+    ///
+    /// `contents` are stored as published so `signature` keeps verifying on later loads.
+    pub fn install(
+        paths: &MmsPaths,
+        contents: &str,
+        signature: Option<&str>,
+        allow_unsigned: bool,
+    ) -> Result<(), AppError> {
+        paths.ensure_config_dir()?;
+        let master_path = paths.master_catalogue_path();
+        if master_path.exists() && Self::read_installed(paths).is_none() {
+            let quarantined = Self::quarantine(paths)?;
+            eprintln!("ℹ️  Moved the unreadable master catalogue to {}", quarantined.display());
+        }
+        fs::write(&master_path, contents)?;
+        let signature_path = PathBuf::from(format!("{}{SIGNATURE_SUFFIX}", master_path.display()));
+        match signature {
+            Some(signature) => fs::write(&signature_path, signature)?,
+            None => remove_if_exists(&signature_path)?,
+        }
+        match allow_unsigned {
+            true => fs::write(paths.unsigned_master_marker_path(), "")?,
+            false => remove_if_exists(&paths.unsigned_master_marker_path())?,
+        }
+        remove_if_exists(&paths.embedded_master_marker_path())?;
+        Self::clear_quarantine_marker(paths)
+    }
+
+    fn clear_quarantine_marker(paths: &MmsPaths) -> Result<(), AppError> {
+        remove_if_exists(&paths.quarantine_marker_path())
+    }
+
     /// Overwrite the stored master catalogue with the embedded version.
+    ///
+    /// Its hash is recorded so the copy stays trusted after upgrading to a newer mms.
     pub fn write_embedded(paths: &MmsPaths) -> Result<(), AppError> {
-        let parsed: McpCatalogue = serde_json::from_str(Self::EMBEDDED_JSON)?;
-        let serialised = serde_json::to_string_pretty(&parsed)?;
-        paths.ensure_config_dir()?;
-        fs::write(paths.master_catalogue_path(), format!("{serialised}\n"))?;
+        Self::install(paths, Self::EMBEDDED_JSON, None, false)?;
+        fs::write(paths.embedded_master_marker_path(), format!("{}\n", Self::EMBEDDED_SHA256))?;
         Ok(())
     }
 }
 
+fn remove_if_exists(path: &Path) -> Result<(), AppError> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// What to do when the stored master catalogue cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Recovery {
+    /// Keep the quarantined copy and fail with instructions for fixing it.
+    #[default]
+    Refuse,
+    /// Restore the embedded copy (`--repair`, or confirmed when asked).
+    Repair,
+}
+
+/// Whether `contents` parse as JSON declaring a schema newer than this build understands.
+fn newer_schema(contents: &str) -> bool {
+    serde_json::from_str::<Value>(contents)
+        .is_ok_and(|doc| migrate::schema_version(&doc).is_ok_and(|v| v > CURRENT_SCHEMA_VERSION))
+}
+
+/// Describe a parse failure, with its line and column when it is a syntax problem.
+fn describe_error(path: &Path, contents: &str, err: &AppError) -> String {
+    validate::validate_str(path, contents)
+        .into_iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.to_string())
+        .unwrap_or_else(|| format!("{}: {err}", path.display()))
+}
+
+/// How one server differs between two catalogues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerChange {
+    Added(String),
+    Removed(String),
+    /// The server exists in both; lists the top-level fields that differ.
+    Changed(String, Vec<String>),
+}
+
+/// Compare two catalogues server by server.
+pub fn diff(old: &McpCatalogue, new: &McpCatalogue) -> Vec<ServerChange> {
+    let mut changes = Vec::new();
+    for (name, server) in &new.mcp_servers {
+        let Some(previous) = old.mcp_servers.get(name) else {
+            changes.push(ServerChange::Added(name.clone()));
+            continue;
+        };
+        let fields = changed_fields(previous, server);
+        if !fields.is_empty() {
+            changes.push(ServerChange::Changed(name.clone(), fields));
+        }
+    }
+    for name in old.mcp_servers.keys().filter(|name| !new.mcp_servers.contains_key(*name)) {
+        changes.push(ServerChange::Removed(name.clone()));
+    }
+    changes
+}
+
+/// The top-level fields, such as `args` or `env`, that differ between two entries.
+pub fn changed_fields(old: &McpServer, new: &McpServer) -> Vec<String> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    let mut fields: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +397,31 @@ mod tests {
             assert!(seen_names.insert(name), "Duplicate server name: {}", name);
         }
     }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_fields() {
+        let old: McpCatalogue = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "kept": { "command": "a", "args": ["1"] },
+                "gone": { "command": "b" }
+            }
+        }))
+        .unwrap();
+        let new: McpCatalogue = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "kept": { "command": "a", "args": ["2"], "description": "d" },
+                "fresh": { "command": "c" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                ServerChange::Added("fresh".into()),
+                ServerChange::Changed("kept".into(), vec!["args".into(), "description".into()]),
+                ServerChange::Removed("gone".into()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,264 @@
+use super::model::{McpCatalogue, McpServer};
+use crate::error::AppError;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Pseudo-field naming a whole server, used when one side deleted it.
+pub const WHOLE_SERVER: &str = "(server)";
+
+/// Object fields merged key by key rather than as a whole.
+const NESTED_FIELDS: &[&str] = &["env", "headers"];
+
+type Fields = BTreeMap<String, Value>;
+
+/// A field both the user and the new master changed in different ways.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub server: String,
+    /// Field such as `args` or `env.TOKEN`, or [`WHOLE_SERVER`].
+    pub field: String,
+    pub base: Option<Value>,
+    pub mine: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// Result of merging a new master into the user's global catalogue.
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// The user's catalogue with every non-conflicting master change applied.
+    pub merged: McpCatalogue,
+    /// Master changes that were taken, e.g. `~ github args`.
+    pub applied: Vec<String>,
+    /// Conflicts, currently resolved in favour of the user's version.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Three-way merge of `theirs` (new master) into `mine` (current global) relative to `base`,
+/// the master `mine` was generated from. Without a base every difference is a conflict.
+pub fn three_way(
+    base: Option<&McpCatalogue>,
+    mine: &McpCatalogue,
+    theirs: &McpCatalogue,
+) -> Result<MergeOutcome, AppError> {
+    let servers = |catalogue: Option<&McpCatalogue>| -> Result<BTreeMap<String, Fields>, AppError> {
+        let mut flattened = BTreeMap::new();
+        for (name, server) in catalogue.map(|c| &c.mcp_servers).into_iter().flatten() {
+            flattened.insert(name.clone(), flatten(server)?);
+        }
+        Ok(flattened)
+    };
+    let (base, mine_fields, theirs_fields) =
+        (servers(base)?, servers(Some(mine))?, servers(Some(theirs))?);
+
+    let mut merged = mine.clone();
+    let mut applied = Vec::new();
+    let mut conflicts = Vec::new();
+    let names: BTreeSet<&String> =
+        base.keys().chain(mine_fields.keys()).chain(theirs_fields.keys()).collect();
+
+    for name in names {
+        let whole = |fields: Option<&Fields>| fields.map(|f| Value::Object(unflatten_map(f)));
+        match (base.get(name), mine_fields.get(name), theirs_fields.get(name)) {
+            (base_fields, Some(mine_server), Some(theirs_server)) => {
+                let empty = Fields::new();
+                let base_server = base_fields.unwrap_or(&empty);
+                let mut result = mine_server.clone();
+                let keys: BTreeSet<&String> = base_server
+                    .keys()
+                    .chain(mine_server.keys())
+                    .chain(theirs_server.keys())
+                    .collect();
+                for key in keys {
+                    let (b, m, t) =
+                        (base_server.get(key), mine_server.get(key), theirs_server.get(key));
+                    if m == t || t == b {
+                        continue;
+                    }
+                    if m == b {
+                        match t {
+                            Some(value) => result.insert(key.clone(), value.clone()),
+                            None => result.remove(key),
+                        };
+                        applied.push(format!("~ {name} {key}"));
+                    } else {
+                        conflicts.push(Conflict {
+                            server: name.clone(),
+                            field: key.clone(),
+                            base: b.cloned(),
+                            mine: m.cloned(),
+                            theirs: t.cloned(),
+                        });
+                    }
+                }
+                merged.mcp_servers.insert(name.clone(), unflatten(&result)?);
+            }
+            (Some(base_server), Some(mine_server), None) => {
+                if mine_server == base_server {
+                    merged.mcp_servers.remove(name);
+                    merged.mark_resolved(name, false);
+                    applied.push(format!("- {name}"));
+                } else {
+                    conflicts.push(Conflict {
+                        server: name.clone(),
+                        field: WHOLE_SERVER.to_string(),
+                        base: whole(Some(base_server)),
+                        mine: whole(Some(mine_server)),
+                        theirs: None,
+                    });
+                }
+            }
+            (Some(base_server), None, Some(theirs_server)) => {
+                if theirs_server != base_server {
+                    conflicts.push(Conflict {
+                        server: name.clone(),
+                        field: WHOLE_SERVER.to_string(),
+                        base: whole(Some(base_server)),
+                        mine: None,
+                        theirs: whole(Some(theirs_server)),
+                    });
+                }
+            }
+            (None, None, Some(_)) => {
+                merged.mcp_servers.insert(name.clone(), theirs.mcp_servers[name].clone());
+                applied.push(format!("+ {name}"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(MergeOutcome { merged, applied, conflicts })
+}
+
+/// Apply the master's side of a conflict to `catalogue`.
+pub fn take_theirs(catalogue: &mut McpCatalogue, conflict: &Conflict) -> Result<(), AppError> {
+    if conflict.field == WHOLE_SERVER {
+        match &conflict.theirs {
+            Some(server) => {
+                let server: McpServer = serde_json::from_value(server.clone())?;
+                catalogue.mcp_servers.insert(conflict.server.clone(), server);
+            }
+            None => {
+                catalogue.mcp_servers.remove(&conflict.server);
+                catalogue.mark_resolved(&conflict.server, false);
+            }
+        }
+        return Ok(());
+    }
+    let Some(server) = catalogue.mcp_servers.get(&conflict.server) else { return Ok(()) };
+    let mut fields = flatten(server)?;
+    match &conflict.theirs {
+        Some(value) => fields.insert(conflict.field.clone(), value.clone()),
+        None => fields.remove(&conflict.field),
+    };
+    catalogue.mcp_servers.insert(conflict.server.clone(), unflatten(&fields)?);
+    Ok(())
+}
+
+fn flatten(server: &McpServer) -> Result<Fields, AppError> {
+    let Value::Object(object) = serde_json::to_value(server)? else {
+        return Err(AppError::config("MCP server did not serialise to an object"));
+    };
+    let mut fields = Fields::new();
+    for (key, value) in object {
+        match value {
+            Value::Object(entries) if NESTED_FIELDS.contains(&key.as_str()) => {
+                for (entry, value) in entries {
+                    fields.insert(format!("{key}.{entry}"), value);
+                }
+            }
+            value => {
+                fields.insert(key, value);
+            }
+        }
+    }
+    Ok(fields)
+}
+
+fn unflatten_map(fields: &Fields) -> Map<String, Value> {
+    let mut object = Map::new();
+    for (key, value) in fields {
+        let nested = key.split_once('.').filter(|(parent, _)| NESTED_FIELDS.contains(parent));
+        match nested {
+            Some((parent, entry)) => {
+                let parent =
+                    object.entry(parent.to_string()).or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(entries) = parent {
+                    entries.insert(entry.to_string(), value.clone());
+                }
+            }
+            None => {
+                object.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    object
+}
+
+fn unflatten(fields: &Fields) -> Result<McpServer, AppError> {
+    Ok(serde_json::from_value(Value::Object(unflatten_map(fields)))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn catalogue(value: Value) -> McpCatalogue {
+        serde_json::from_value(json!({ "mcpServers": value })).unwrap()
+    }
+
+    #[test]
+    fn merges_independent_changes_per_field() {
+        let base = catalogue(json!({
+            "gh": { "command": "npx", "args": ["gh@1"], "env": { "A": "1", "B": "1" } },
+            "old": { "command": "old" },
+            "dropped": { "command": "dropped" }
+        }));
+        let mine = catalogue(json!({
+            "gh": { "command": "npx", "args": ["gh@1"], "env": { "A": "mine", "B": "1" } },
+            "old": { "command": "old" },
+            "custom": { "command": "custom" }
+        }));
+        let theirs = catalogue(json!({
+            "gh": { "command": "npx", "args": ["gh@2"], "env": { "A": "1", "B": "2" } },
+            "dropped": { "command": "dropped" },
+            "fresh": { "command": "fresh" }
+        }));
+
+        let outcome = three_way(Some(&base), &mine, &theirs).unwrap();
+        assert!(outcome.conflicts.is_empty(), "{:?}", outcome.conflicts);
+        let gh = &outcome.merged.mcp_servers["gh"];
+        assert_eq!(gh.args, vec!["gh@2"]);
+        assert_eq!(gh.env["A"], "mine");
+        assert_eq!(gh.env["B"], "2");
+        let names: Vec<&String> = outcome.merged.mcp_servers.keys().collect();
+        assert_eq!(names, ["custom", "fresh", "gh"]);
+    }
+
+    #[test]
+    fn reports_conflicts_and_can_take_theirs() {
+        let base = catalogue(json!({ "gh": { "command": "npx", "args": ["gh@1"] } }));
+        let mine = catalogue(json!({ "gh": { "command": "npx", "args": ["gh@mine"] } }));
+        let theirs = catalogue(json!({ "gh": { "command": "npx", "args": ["gh@2"] } }));
+
+        let mut outcome = three_way(Some(&base), &mine, &theirs).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].field, "args");
+        assert_eq!(outcome.merged.mcp_servers["gh"].args, vec!["gh@mine"]);
+
+        let conflict = outcome.conflicts.remove(0);
+        take_theirs(&mut outcome.merged, &conflict).unwrap();
+        assert_eq!(outcome.merged.mcp_servers["gh"].args, vec!["gh@2"]);
+    }
+
+    #[test]
+    fn deletion_against_modification_conflicts() {
+        let base = catalogue(json!({ "gh": { "command": "a" } }));
+        let mine = catalogue(json!({ "gh": { "command": "edited" } }));
+        let theirs = catalogue(json!({}));
+
+        let outcome = three_way(Some(&base), &mine, &theirs).unwrap();
+        assert_eq!(outcome.conflicts[0].field, WHOLE_SERVER);
+        assert!(outcome.merged.mcp_servers.contains_key("gh"));
+    }
+}
@@ -0,0 +1,137 @@
+use super::model::{McpCatalogue, CURRENT_SCHEMA_VERSION};
+use super::sensitive;
+use crate::error::AppError;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Upgrade step from schema version `n` (its index) to `n + 1`.
+type Migration = fn(&mut Map<String, Value>);
+
+/// Ordered migration pipeline; `MIGRATIONS[n]` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [v0_to_v1];
+
+/// Outcome of inspecting a catalogue file without modifying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStatus {
+    Current,
+    Outdated(u32),
+}
+
+/// Read the schema version of a raw catalogue document (files without one are version 0).
+pub fn schema_version(doc: &Value) -> Result<u32, AppError> {
+    let Some(version) = doc.get("mms").and_then(|meta| meta.get("schemaVersion")) else {
+        return Ok(0);
+    };
+    version.as_u64().and_then(|version| u32::try_from(version).ok()).ok_or_else(|| {
+        AppError::config(format!("catalogue schema version {version} is not a valid version"))
+    })
+}
+
+/// Upgrade a raw catalogue document in place, returning whether any migration ran.
+pub fn migrate(doc: &mut Value) -> Result<bool, AppError> {
+    let version = schema_version(doc)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(AppError::config(format!(
+            "catalogue schema version {version} is newer than this mms supports \
+             ({CURRENT_SCHEMA_VERSION}); upgrade mms"
+        )));
+    }
+    let Value::Object(map) = doc else {
+        return Err(AppError::config("catalogue root must be a JSON object"));
+    };
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(map);
+    }
+    Ok(version < CURRENT_SCHEMA_VERSION)
+}
+
+/// Parse catalogue JSON, applying migrations, and report whether the source was outdated.
+pub fn parse_catalogue(contents: &str) -> Result<(McpCatalogue, bool), AppError> {
+    let mut doc: Value = serde_json::from_str(contents)?;
+    let upgraded = migrate(&mut doc)?;
+    Ok((serde_json::from_value(doc)?, upgraded))
+}
+
+/// Load a catalogue file, upgrading it in memory only; the file is left as it is.
+pub fn read_catalogue(path: &Path) -> Result<McpCatalogue, AppError> {
+    let contents = fs::read_to_string(path)?;
+    parse_catalogue(&contents).map(|(catalogue, _)| catalogue)
+}
+
+/// Rewrite a catalogue file in the current schema.
+pub fn migrate_file(path: &Path) -> Result<(), AppError> {
+    write_catalogue(path, &read_catalogue(path)?)
+}
+
+/// Inspect a catalogue file and report whether it needs upgrading.
+pub fn check_file(path: &Path) -> Result<MigrationStatus, AppError> {
+    let doc: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let version = schema_version(&doc)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(AppError::config(format!(
+            "{} uses schema version {version}, newer than this mms supports",
+            path.display()
+        )));
+    }
+    if version < CURRENT_SCHEMA_VERSION {
+        Ok(MigrationStatus::Outdated(version))
+    } else {
+        Ok(MigrationStatus::Current)
+    }
+}
+
+/// Serialise a catalogue in the canonical pretty-printed form used for every scope.
+///
+/// Catalogues holding resolved secrets are written with owner-only permissions.
+pub fn write_catalogue(path: &Path, catalogue: &McpCatalogue) -> Result<(), AppError> {
+    let serialised = format!("{}\n", serde_json::to_string_pretty(catalogue)?);
+    if catalogue.holds_secrets() {
+        sensitive::write_private(path, &serialised)
+    } else {
+        fs::write(path, serialised)?;
+        Ok(())
+    }
+}
+
+/// Version 0 covers catalogues written before mms tracked a schema version.
+fn v0_to_v1(map: &mut Map<String, Value>) {
+    map.entry("mcpServers").or_insert_with(|| json!({}));
+    set_version(map, 1);
+}
+
+fn set_version(map: &mut Map<String, Value>, version: u32) {
+    let meta = map.entry("mms").or_insert_with(|| json!({}));
+    if !meta.is_object() {
+        *meta = json!({});
+    }
+    meta["schemaVersion"] = json!(version);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_catalogue_is_upgraded() {
+        let (catalogue, upgraded) =
+            parse_catalogue(r#"{"mcpServers": {"a": {"command": "x"}}}"#).unwrap();
+        assert!(upgraded);
+        assert_eq!(catalogue.meta.schema_version, CURRENT_SCHEMA_VERSION);
+        assert!(catalogue.mcp_servers.contains_key("a"));
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let future = format!(r#"{{"mcpServers": {{}}, "mms": {{"schemaVersion": {}}}}}"#, 99);
+        assert!(parse_catalogue(&future).is_err());
+    }
+
+    #[test]
+    fn oversized_schema_versions_are_rejected() {
+        let wrapped = r#"{"mcpServers": {}, "mms": {"schemaVersion": 4294967297}}"#;
+        let err = parse_catalogue(wrapped).unwrap_err().to_string();
+        assert!(err.contains("4294967297 is not a valid version"), "{err}");
+        assert!(parse_catalogue(r#"{"mcpServers": {}, "mms": {"schemaVersion": -1}}"#).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use super::model::McpServer;
+use super::paths::MmsPaths;
+use super::placeholder::{self, Fallback, Segment, VariableSource};
+use super::secret_store::SecretStore;
+use super::settings::MmsSettings;
+use super::variables::{self, Variables, PROJECT_ENV_FILE};
+use crate::error::AppError;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// A variable that some servers reference but no layer provides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingVariable {
+    pub name: String,
+    pub servers: Vec<String>,
+    /// Some placeholder uses `${VAR:?message}`, so expanding without a value fails.
+    pub required: bool,
+}
+
+/// Collect `${VAR}` and `${VAR:?...}` placeholders that have no value.
+///
+/// Placeholders with a `:-` default are never missing.
+pub fn find_missing<'a>(
+    servers: impl IntoIterator<Item = (&'a String, &'a McpServer)>,
+    vars: &dyn VariableSource,
+) -> Vec<MissingVariable> {
+    let mut missing: Vec<MissingVariable> = Vec::new();
+    for (server_name, server) in servers {
+        for (_, segment) in placeholder::server_placeholders(server) {
+            let Segment::Variable { name, fallback, .. } = segment else { continue };
+            let (is_set, required) = match fallback {
+                Fallback::Default(_) => continue,
+                Fallback::Keep => (vars.lookup(&name).is_some(), false),
+                Fallback::Required(_) => (vars.lookup(&name).is_some_and(|v| !v.is_empty()), true),
+            };
+            if is_set {
+                continue;
+            }
+            match missing.iter_mut().find(|entry| entry.name == name) {
+                Some(entry) => {
+                    entry.required |= required;
+                    if !entry.servers.contains(server_name) {
+                        entry.servers.push(server_name.clone());
+                    }
+                }
+                None => missing.push(MissingVariable {
+                    name,
+                    servers: vec![server_name.clone()],
+                    required,
+                }),
+            }
+        }
+    }
+    missing
+}
+
+/// Ask for each missing value on the terminal, optionally saving it for later runs.
+///
+/// Values entered are used for the rest of this run. Without a terminal this fails with the
+/// full list of missing variables instead.
+pub fn supply_missing(
+    missing: &[MissingVariable],
+    paths: &MmsPaths,
+    settings: &MmsSettings,
+    vars: &Variables,
+    workspace: &Path,
+) -> Result<(), AppError> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        let list: Vec<String> = missing
+            .iter()
+            .map(|entry| format!("  - {} (needed by {})", entry.name, entry.servers.join(", ")))
+            .collect();
+        return Err(AppError::config(format!(
+            "missing values for placeholders:\n{}\nSet them in the environment, {PROJECT_ENV_FILE} \
+             or {}, or run in a terminal to be prompted",
+            list.join("\n"),
+            paths.user_env_path().display()
+        )));
+    }
+
+    let project_env = workspace.join(PROJECT_ENV_FILE);
+    for entry in missing {
+        eprintln!("\n{} is not set (needed by {})", entry.name, entry.servers.join(", "));
+        let value =
+            rpassword::prompt_password(format!("Value for {} (empty to skip): ", entry.name))?;
+        if value.is_empty() {
+            eprintln!("ℹ️  Leaving ${{{}}} unresolved", entry.name);
+            continue;
+        }
+
+        eprintln!("Save {} to:", entry.name);
+        eprintln!("  1) secret store ({})", paths.secret_store_path().display());
+        eprintln!("  2) project env file ({})", project_env.display());
+        eprintln!("  3) user env file ({})", paths.user_env_path().display());
+        eprintln!("  4) nowhere, use it for this run only");
+        eprint!("Choice [1]: ");
+        std::io::stderr().flush()?;
+        let mut choice = String::new();
+        std::io::stdin().lock().read_line(&mut choice)?;
+
+        match choice.trim() {
+            "" | "1" => {
+                let store = SecretStore::open(paths, settings, None);
+                let mut secrets = store.read()?;
+                secrets.insert(entry.name.clone(), value.clone());
+                store.write(&secrets)?;
+                eprintln!("✅ Stored {} in the secret store", entry.name);
+            }
+            "2" => {
+                variables::set_env_value(&project_env, &entry.name, &value)?;
+                eprintln!("✅ Saved {} to {}", entry.name, project_env.display());
+            }
+            "3" => {
+                let user_env = paths.user_env_path();
+                variables::set_env_value(&user_env, &entry.name, &value)?;
+                eprintln!("✅ Saved {} to {}", entry.name, user_env.display());
+            }
+            _ => {}
+        }
+        vars.remember(&entry.name, value);
+    }
+    Ok(())
+}
@@ -1,13 +1,35 @@
 //! Configuration helpers for managing MCP catalogues across master, global, and local scopes.
 
 pub mod global;
+pub mod layers;
 pub mod local;
+pub mod lock;
 pub mod master;
+pub mod merge;
+pub mod migrate;
+pub mod missing;
 pub mod model;
 pub mod paths;
+pub mod placeholder;
+pub mod providers;
+pub mod redact;
+pub mod registry;
+pub mod schema;
+pub mod search;
+pub mod secret_store;
+pub mod sensitive;
+pub mod settings;
+pub mod signature;
+pub mod sources;
+pub mod trust;
+pub mod validate;
+pub mod variables;
 
 pub use global::GlobalCatalogue;
+pub use layers::{CatalogueStack, Layer};
 pub use local::LocalCatalogue;
 pub use master::MasterCatalogue;
-pub use model::{McpCatalogue, McpServer};
+pub use model::{CatalogueMeta, McpCatalogue, McpServer};
 pub use paths::MmsPaths;
+pub use settings::{MmsSettings, PlaceholderMode};
+pub use variables::Variables;
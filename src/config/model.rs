@@ -1,35 +1,121 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 
+/// Catalogue format version written by this build; see [`crate::config::migrate`].
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
 /// Representation of the MCP server catalogue shared across scopes.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct McpCatalogue {
     #[serde(rename = "mcpServers", default)]
     pub mcp_servers: BTreeMap<String, McpServer>,
+    /// mms bookkeeping kept outside `mcpServers` so Claude-compatible readers ignore it.
+    #[serde(rename = "mms", default)]
+    pub meta: CatalogueMeta,
     #[serde(flatten, default)]
     pub extras: Map<String, Value>,
 }
 
+/// Metadata block stored under the top-level `mms` key of every catalogue mms writes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogueMeta {
+    pub schema_version: u32,
+    /// Servers whose placeholders were replaced by real values when this file was written.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_servers: Vec<String>,
+    /// SHA-256 of the master this catalogue came from: the installed source for the master
+    /// itself, and the master it was generated from for `~/.mcp.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master_hash: Option<String>,
+    /// Entries copied from the global catalogue, with the fingerprint they had when copied.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub copied_from_global: BTreeMap<String, String>,
+    #[serde(flatten, default)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for CatalogueMeta {
+    fn default() -> Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            resolved_servers: Vec::new(),
+            master_hash: None,
+            copied_from_global: BTreeMap::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
 impl McpCatalogue {
     pub fn empty() -> Self {
         Self::default()
     }
+
+    /// Whether the catalogue carries resolved placeholder values and must be kept private.
+    pub fn holds_secrets(&self) -> bool {
+        !self.meta.resolved_servers.is_empty()
+    }
+
+    /// Record whether `name` carries resolved placeholder values.
+    pub fn mark_resolved(&mut self, name: &str, resolved: bool) {
+        let list = &mut self.meta.resolved_servers;
+        list.retain(|entry| entry != name);
+        if resolved && self.mcp_servers.contains_key(name) {
+            list.push(name.to_string());
+            list.sort();
+        }
+    }
+
+    /// Record that `name` was copied from `source` in the global catalogue, or forget it.
+    pub fn mark_copied(&mut self, name: &str, source: Option<&McpServer>) {
+        match source {
+            Some(server) => {
+                self.meta.copied_from_global.insert(name.to_string(), server.fingerprint());
+            }
+            None => {
+                self.meta.copied_from_global.remove(name);
+            }
+        }
+    }
+
+    /// Whether any entry inherits from the global catalogue via `extends`.
+    pub fn has_references(&self) -> bool {
+        self.mcp_servers.values().any(|server| server.extends.is_some())
+    }
 }
 
 /// Configuration for a single MCP server entry.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct McpServer {
+    /// Name of a global catalogue entry this server inherits from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Transport used to reach the server.
     #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
     pub server_type: Option<String>,
+    /// Executable launched for stdio servers.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub env: BTreeMap<String, String>,
+    /// Working directory for stdio servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Endpoint for `sse` and `http` servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// HTTP headers sent to `url`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Startup timeout in seconds.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u64>,
     #[serde(flatten, default)]
@@ -37,6 +123,54 @@ pub struct McpServer {
 }
 
 impl McpServer {
+    /// Create an entry that references the named global server without copying it.
+    pub fn reference(name: &str) -> Self {
+        Self { extends: Some(name.to_string()), ..Self::default() }
+    }
+
+    /// SHA-256 of the entry's canonical JSON, used to notice later edits.
+    pub fn fingerprint(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("{:x}", Sha256::digest(json.as_bytes()))
+    }
+
+    /// Overlay the fields set on this entry onto `base`, merging `env` and extras per key.
+    pub fn merged_over(&self, base: &McpServer) -> McpServer {
+        let mut merged = base.clone();
+        merged.extends = None;
+        if self.server_type.is_some() {
+            merged.server_type = self.server_type.clone();
+        }
+        if self.command.is_some() {
+            merged.command = self.command.clone();
+        }
+        if !self.args.is_empty() {
+            merged.args = self.args.clone();
+        }
+        for (key, value) in &self.env {
+            merged.env.insert(key.clone(), value.clone());
+        }
+        if self.cwd.is_some() {
+            merged.cwd = self.cwd.clone();
+        }
+        if self.url.is_some() {
+            merged.url = self.url.clone();
+        }
+        for (key, value) in &self.headers {
+            merged.headers.insert(key.clone(), value.clone());
+        }
+        if self.description.is_some() {
+            merged.description = self.description.clone();
+        }
+        if self.timeout.is_some() {
+            merged.timeout = self.timeout;
+        }
+        for (key, value) in &self.extra {
+            merged.extra.insert(key.clone(), value.clone());
+        }
+        merged
+    }
+
     /// Compose the launch command for display purposes.
     pub fn render_command(&self) -> Option<String> {
         let base = self.command.as_ref()?.trim();
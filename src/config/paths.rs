@@ -35,6 +35,58 @@ impl MmsPaths {
         self.config_dir.join("master.json")
     }
 
+    pub fn settings_path(&self) -> PathBuf {
+        self.config_dir.join("config.toml")
+    }
+
+    pub fn user_env_path(&self) -> PathBuf {
+        self.config_dir.join("env")
+    }
+
+    /// Directory holding unreadable catalogues moved aside by mms.
+    pub fn quarantine_dir(&self) -> PathBuf {
+        self.config_dir.join("quarantine")
+    }
+
+    /// Marker recording a quarantined master that has not been fixed or reset yet.
+    pub fn quarantine_marker_path(&self) -> PathBuf {
+        self.config_dir.join("master.quarantined")
+    }
+
+    /// SHA-256 of the master mms last installed from its own embedded copy.
+    pub fn embedded_master_marker_path(&self) -> PathBuf {
+        self.config_dir.join("master.embedded")
+    }
+
+    /// Marker recording that the installed master was accepted with `--allow-unsigned`.
+    pub fn unsigned_master_marker_path(&self) -> PathBuf {
+        self.config_dir.join("master.unsigned")
+    }
+
+    /// Snapshot of the master catalogue `~/.mcp.json` was last generated from.
+    pub fn global_base_path(&self) -> PathBuf {
+        self.config_dir.join("global.base.json")
+    }
+
+    /// Checkouts of team catalogues cloned by `mms catalogue subscribe`.
+    pub fn sources_dir(&self) -> PathBuf {
+        self.config_dir.join("sources")
+    }
+
+    /// Minisign public keys whose signatures on catalogues are accepted.
+    pub fn trusted_keys_path(&self) -> PathBuf {
+        self.config_dir.join("trusted_keys")
+    }
+
+    /// Server definitions approved for `sync`.
+    pub fn trust_path(&self) -> PathBuf {
+        self.config_dir.join("trust.json")
+    }
+
+    pub fn secret_store_path(&self) -> PathBuf {
+        self.config_dir.join("secrets.enc")
+    }
+
     pub fn global_catalogue_path(&self) -> PathBuf {
         self.home.join(".mcp.json")
     }
@@ -0,0 +1,447 @@
+use super::model::{McpCatalogue, McpServer};
+pub use super::providers::Provider;
+use super::secret_store::is_secret_name;
+use crate::error::AppError;
+
+/// Source of values for `${VAR}` placeholders.
+pub trait VariableSource {
+    fn lookup(&self, name: &str) -> Option<String>;
+
+    /// Resolve `${cmd:...}` and `${secret:...}` placeholders.
+    fn provide(&self, provider: &Provider) -> Result<String, String> {
+        Err(format!("`{provider}` placeholders are not supported here"))
+    }
+
+    /// Whether provider placeholders are resolved at all; when not they are kept as written.
+    fn runs_providers(&self) -> bool {
+        true
+    }
+}
+
+/// Resolves variables from another source but leaves `${cmd:...}` and `${secret:...}` alone.
+///
+/// Used wherever a catalogue is expanded outside `sync`, so no command runs on its behalf.
+pub struct VariablesOnly<'a>(pub &'a dyn VariableSource);
+
+impl VariableSource for VariablesOnly<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.0.lookup(name)
+    }
+
+    fn runs_providers(&self) -> bool {
+        false
+    }
+}
+
+/// Resolves placeholders from the process environment.
+pub struct ProcessEnv;
+
+impl VariableSource for ProcessEnv {
+    fn lookup(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+/// How a placeholder treats a missing or empty variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fallback {
+    /// `${VAR}`: left untouched so downstream tools can still interpret it.
+    Keep,
+    /// `${VAR:-default}`: replaced by the default when unset or empty.
+    Default(String),
+    /// `${VAR:?message}`: an error when unset or empty.
+    Required(String),
+}
+
+/// One piece of a parsed placeholder string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Literal(String),
+    Variable { name: String, fallback: Fallback, raw: String },
+    Provider { provider: Provider, raw: String },
+}
+
+/// Split `input` into literal text and placeholders.
+///
+/// Supports `${VAR}`, `${VAR:-default}`, `${VAR:?message}`, `${cmd:...}` and `${secret:NAME}`
+/// anywhere in the string and `$$` as an escaped `$`. A `$` that starts neither form is kept
+/// literally. Defaults may hold placeholders of their own, and braces inside quotes of a
+/// `${cmd:...}` command do not end it.
+pub fn parse(input: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = input;
+
+    while let Some(index) = rest.find('$') {
+        literal.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            literal.push('$');
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end =
+                closing_brace(body).ok_or_else(|| "unterminated `${` placeholder".to_string())?;
+            let expression = &body[..end];
+            let raw = format!("${{{expression}}}");
+            let segment = match parse_provider(expression)? {
+                Some(provider) => Segment::Provider { provider, raw },
+                None => {
+                    let (name, fallback) = parse_expression(expression)?;
+                    Segment::Variable { name, fallback, raw }
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(segment);
+            rest = &body[end + 1..];
+        } else {
+            literal.push('$');
+            rest = after;
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+/// Index of the `}` that ends a placeholder body, skipping nested `{...}` pairs.
+///
+/// Quotes and backslash escapes are honoured in commands only, so a default such as
+/// `${GREETING:-it's}` still ends at its brace.
+fn closing_brace(body: &str) -> Option<usize> {
+    let shell = body.starts_with("cmd:");
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut chars = body.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') if shell => {
+                chars.next();
+            }
+            (Some(_), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') if shell => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return Some(index),
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_provider(expression: &str) -> Result<Option<Provider>, String> {
+    if let Some(command) = expression.strip_prefix("cmd:") {
+        if command.trim().is_empty() {
+            return Err("`${cmd:...}` needs a command".to_string());
+        }
+        return Ok(Some(Provider::Command(command.trim().to_string())));
+    }
+    if let Some(name) = expression.strip_prefix("secret:") {
+        if !is_secret_name(name) {
+            return Err(format!("\"{name}\" is not a valid secret name"));
+        }
+        return Ok(Some(Provider::Secret(name.to_string())));
+    }
+    Ok(None)
+}
+
+fn parse_expression(expression: &str) -> Result<(String, Fallback), String> {
+    let (name, fallback) = if let Some((name, default)) = expression.split_once(":-") {
+        parse(default)?;
+        (name, Fallback::Default(default.to_string()))
+    } else if let Some((name, message)) = expression.split_once(":?") {
+        (name, Fallback::Required(message.to_string()))
+    } else {
+        (expression, Fallback::Keep)
+    };
+
+    if name.is_empty() {
+        return Err("missing variable name".to_string());
+    }
+    if !is_var_name(name) {
+        return Err(format!("\"{name}\" is not a valid variable name"));
+    }
+    Ok((name.to_string(), fallback))
+}
+
+/// Whether `name` is a valid environment variable name.
+pub fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Result of expanding a single string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    pub value: String,
+    /// Whether at least one variable value was substituted into the output.
+    pub substituted: bool,
+}
+
+/// Expand every placeholder in `input` using `vars`.
+pub fn expand(input: &str, vars: &dyn VariableSource) -> Result<Expansion, String> {
+    let mut value = String::new();
+    let mut substituted = false;
+
+    for segment in parse(input)? {
+        match segment {
+            Segment::Literal(text) => value.push_str(&text),
+            Segment::Provider { raw, .. } if !vars.runs_providers() => value.push_str(&raw),
+            Segment::Provider { provider, .. } => {
+                value.push_str(&vars.provide(&provider)?);
+                substituted = true;
+            }
+            Segment::Variable { name, fallback, raw } => {
+                // Like the shell, `:-` and `:?` treat an empty value as unset.
+                let resolved = vars
+                    .lookup(&name)
+                    .filter(|v| !v.is_empty() || matches!(fallback, Fallback::Keep));
+                match (resolved, fallback) {
+                    (Some(resolved), _) => {
+                        value.push_str(&resolved);
+                        substituted = true;
+                    }
+                    (None, Fallback::Keep) => value.push_str(&raw),
+                    (None, Fallback::Default(default)) => {
+                        let default = expand(&default, vars)?;
+                        value.push_str(&default.value);
+                        substituted |= default.substituted;
+                    }
+                    (None, Fallback::Required(message)) => {
+                        let message =
+                            if message.is_empty() { "is required but not set" } else { &message };
+                        return Err(format!("{name}: {message}"));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Expansion { value, substituted })
+}
+
+/// Expand placeholders in every string field of a server that may carry them.
+///
+/// Returns whether any variable value was written into the server.
+pub fn expand_server(
+    name: &str,
+    server: &mut McpServer,
+    vars: &dyn VariableSource,
+) -> Result<bool, AppError> {
+    expand_server_fields(name, server, vars).map_err(AppError::config)
+}
+
+fn expand_server_fields(
+    name: &str,
+    server: &mut McpServer,
+    vars: &dyn VariableSource,
+) -> Result<bool, String> {
+    let mut substituted = false;
+    for_each_field(server, |field, value| {
+        let expansion =
+            expand(value, vars).map_err(|err| format!("MCP server '{name}' {field}: {err}"))?;
+        substituted |= expansion.substituted;
+        *value = expansion.value;
+        Ok(())
+    })?;
+    Ok(substituted)
+}
+
+/// Apply `apply` to every field that may carry placeholders, labelled like `env.KEY`.
+pub fn for_each_field(
+    server: &mut McpServer,
+    mut apply: impl FnMut(&str, &mut String) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Some(command) = server.command.as_mut() {
+        apply("command", command)?;
+    }
+    for arg in server.args.iter_mut() {
+        apply("args", arg)?;
+    }
+    for (key, value) in server.env.iter_mut() {
+        apply(&format!("env.{key}"), value)?;
+    }
+    if let Some(cwd) = server.cwd.as_mut() {
+        apply("cwd", cwd)?;
+    }
+    if let Some(url) = server.url.as_mut() {
+        apply("url", url)?;
+    }
+    for (key, value) in server.headers.iter_mut() {
+        apply(&format!("headers.{key}"), value)?;
+    }
+    Ok(())
+}
+
+/// Every placeholder in a server's expandable fields, labelled with the field it appears in.
+///
+/// Fields that fail to parse are skipped; `mms validate` reports those.
+pub fn server_placeholders(server: &McpServer) -> Vec<(String, Segment)> {
+    let mut fields: Vec<(String, &String)> = Vec::new();
+    fields.extend(server.command.iter().map(|command| ("command".to_string(), command)));
+    fields.extend(server.args.iter().map(|arg| ("args".to_string(), arg)));
+    fields.extend(server.env.iter().map(|(key, value)| (format!("env.{key}"), value)));
+    fields.extend(server.cwd.iter().map(|cwd| ("cwd".to_string(), cwd)));
+    fields.extend(server.url.iter().map(|url| ("url".to_string(), url)));
+    fields.extend(server.headers.iter().map(|(key, value)| (format!("headers.{key}"), value)));
+
+    fields
+        .into_iter()
+        .flat_map(|(field, value)| {
+            parse(value)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|segment| !matches!(segment, Segment::Literal(_)))
+                .map(move |segment| (field.clone(), segment))
+        })
+        .collect()
+}
+
+/// Expand placeholders in every server, recording which ones received values.
+///
+/// Every server is attempted so that all failures are reported together.
+pub fn expand_catalogue(
+    catalogue: &mut McpCatalogue,
+    vars: &dyn VariableSource,
+) -> Result<(), AppError> {
+    let mut resolved = Vec::new();
+    let mut failures = Vec::new();
+    for (name, server) in catalogue.mcp_servers.iter_mut() {
+        match expand_server_fields(name, server, vars) {
+            Ok(true) => resolved.push(name.clone()),
+            Ok(false) => {}
+            Err(err) => failures.push(format!("  - {err}")),
+        }
+    }
+    if !failures.is_empty() {
+        return Err(AppError::config(format!(
+            "could not resolve placeholders:\n{}",
+            failures.join("\n")
+        )));
+    }
+    for name in resolved {
+        catalogue.mark_resolved(&name, true);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Vars(HashMap<&'static str, &'static str>);
+
+    impl VariableSource for Vars {
+        fn lookup(&self, name: &str) -> Option<String> {
+            self.0.get(name).map(|v| v.to_string())
+        }
+    }
+
+    fn vars() -> Vars {
+        Vars(HashMap::from([("TOKEN", "abc"), ("EMPTY", "")]))
+    }
+
+    fn expanded(input: &str) -> Result<String, String> {
+        expand(input, &vars()).map(|e| e.value)
+    }
+
+    #[test]
+    fn expands_embedded_placeholders() {
+        assert_eq!(expanded("Bearer ${TOKEN}").unwrap(), "Bearer abc");
+        assert_eq!(expanded("${TOKEN}-${TOKEN}").unwrap(), "abc-abc");
+        assert_eq!(expanded("${MISSING}").unwrap(), "${MISSING}");
+    }
+
+    #[test]
+    fn applies_defaults_and_requirements() {
+        assert_eq!(expanded("${MISSING:-fallback}").unwrap(), "fallback");
+        assert_eq!(expanded("${EMPTY:-fallback}").unwrap(), "fallback");
+        assert_eq!(expanded("${TOKEN:-fallback}").unwrap(), "abc");
+        assert_eq!(expanded("${MISSING:?set it}").unwrap_err(), "MISSING: set it");
+        assert!(expanded("${MISSING:?}").unwrap_err().contains("is required"));
+    }
+
+    #[test]
+    fn handles_escapes_and_malformed_input() {
+        assert_eq!(expanded("cost $$5 and $HOME").unwrap(), "cost $5 and $HOME");
+        assert_eq!(expanded("$${TOKEN}").unwrap(), "${TOKEN}");
+        assert!(expanded("${TOKEN").is_err());
+        assert!(expanded("${}").is_err());
+        assert!(expanded("${1BAD}").is_err());
+    }
+
+    #[test]
+    fn nests_defaults_and_quoted_braces() {
+        assert_eq!(expanded("${MISSING:-${TOKEN}}").unwrap(), "abc");
+        assert_eq!(expanded("${MISSING:-${OTHER:-x}}/y").unwrap(), "x/y");
+        assert_eq!(expanded("${TOKEN:-${OTHER}}").unwrap(), "abc");
+        assert_eq!(expanded("${MISSING:-${OTHER}}").unwrap(), "${OTHER}");
+        assert_eq!(expanded("${MISSING:-${OTHER:?needed}}").unwrap_err(), "OTHER: needed");
+        assert_eq!(expanded("${MISSING:-it's}").unwrap(), "it's");
+        assert!(expanded("${MISSING:-${1BAD}}").is_err());
+        assert!(expanded("${MISSING:-${TOKEN}").is_err());
+
+        let command = |input: &str| match parse(input).unwrap().as_slice() {
+            [Segment::Provider { provider: Provider::Command(command), .. }] => command.clone(),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(command("${cmd:jq -r '{a: .b}' f.json}"), "jq -r '{a: .b}' f.json");
+        assert_eq!(command(r#"${cmd:echo "}" \}}"#), r#"echo "}" \}"#);
+        assert_eq!(command("${cmd:awk '{ print $1 }'}"), "awk '{ print $1 }'");
+    }
+
+    #[test]
+    fn variables_only_keeps_providers() {
+        let input = "${TOKEN}:${cmd:exit 1}";
+        assert_eq!(expand(input, &VariablesOnly(&vars())).unwrap().value, "abc:${cmd:exit 1}");
+        assert!(expanded(input).is_err());
+    }
+
+    #[test]
+    fn expands_all_server_fields() {
+        let mut server = McpServer {
+            command: Some("${MISSING:-npx}".into()),
+            args: vec!["--token=${TOKEN}".into()],
+            url: Some("https://example.test/${TOKEN}".into()),
+            headers: [("Authorization".to_string(), "Bearer ${TOKEN}".to_string())].into(),
+            ..McpServer::default()
+        };
+        assert!(expand_server("demo", &mut server, &vars()).unwrap());
+        assert_eq!(server.command.as_deref(), Some("npx"));
+        assert_eq!(server.args, vec!["--token=abc"]);
+        assert_eq!(server.url.as_deref(), Some("https://example.test/abc"));
+        assert_eq!(server.headers["Authorization"], "Bearer abc");
+    }
+
+    #[test]
+    fn lists_placeholders_by_field() {
+        let server = McpServer {
+            args: vec!["--token=${TOKEN}".into(), "plain".into()],
+            env: [("KEY".to_string(), "${secret:api}-${LEVEL:-info}".to_string())].into(),
+            ..McpServer::default()
+        };
+        let found: Vec<(String, String)> = server_placeholders(&server)
+            .into_iter()
+            .map(|(field, segment)| match segment {
+                Segment::Variable { raw, .. } | Segment::Provider { raw, .. } => (field, raw),
+                Segment::Literal(_) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("args".to_string(), "${TOKEN}".to_string()),
+                ("env.KEY".to_string(), "${secret:api}".to_string()),
+                ("env.KEY".to_string(), "${LEVEL:-info}".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use super::secret_store::SecretStore;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default time a provider command may run before it is killed.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A placeholder that is resolved by something other than a variable lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provider {
+    /// `${cmd:...}`: the trimmed stdout of a shell command.
+    Command(String),
+    /// `${secret:NAME}`: a command mapped in `[secrets]`, otherwise the `mms secret` store.
+    Secret(String),
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Command(command) => write!(f, "cmd:{command}"),
+            Self::Secret(name) => write!(f, "secret:{name}"),
+        }
+    }
+}
+
+/// Runs provider commands, caching each result for the rest of the invocation.
+#[derive(Debug)]
+pub struct SecretProviders {
+    commands: BTreeMap<String, String>,
+    store: Option<SecretStore>,
+    timeout: Duration,
+    cache: RefCell<HashMap<String, Result<String, String>>>,
+}
+
+impl SecretProviders {
+    pub fn new(
+        commands: BTreeMap<String, String>,
+        store: Option<SecretStore>,
+        timeout: Duration,
+    ) -> Self {
+        Self { commands, store, timeout, cache: RefCell::default() }
+    }
+
+    /// Resolve a provider placeholder to its value.
+    pub fn resolve(&self, provider: &Provider) -> Result<String, String> {
+        let command = match provider {
+            Provider::Command(command) => command.clone(),
+            Provider::Secret(name) => match self.commands.get(name) {
+                Some(command) => command.clone(),
+                None => return self.stored(name),
+            },
+        };
+
+        if let Some(cached) = self.cache.borrow().get(&command) {
+            return cached.clone();
+        }
+        let result = run_command(&command, self.timeout);
+        self.cache.borrow_mut().insert(command, result.clone());
+        result
+    }
+
+    /// Where a provider's value would come from, without running anything.
+    pub fn source(&self, provider: &Provider) -> Option<&'static str> {
+        match provider {
+            Provider::Command(_) => Some("shell command"),
+            Provider::Secret(name) if self.commands.contains_key(name) => Some("[secrets] command"),
+            Provider::Secret(_) => self
+                .store
+                .as_ref()
+                .filter(|store| store.exists())
+                .map(|_| "secret store (not unlocked)"),
+        }
+    }
+
+    /// Every value a provider has produced so far in this run.
+    pub fn resolved_values(&self) -> Vec<String> {
+        self.cache.borrow().values().filter_map(|result| result.as_ref().ok().cloned()).collect()
+    }
+
+    /// A value kept in the secret store under `name`, if the store can be unlocked.
+    pub fn store_value(&self, name: &str) -> Option<String> {
+        let store = self.store.as_ref().filter(|store| store.exists())?;
+        store.get(name).ok().flatten()
+    }
+
+    /// A value kept in the secret store under `name`, if the store is already unlocked.
+    pub fn unlocked_store_value(&self, name: &str) -> Option<String> {
+        self.store.as_ref()?.get_if_unlocked(name)
+    }
+
+    /// Whether a secret store exists that lookups could unlock.
+    pub fn has_store(&self) -> bool {
+        self.store.as_ref().is_some_and(SecretStore::exists)
+    }
+
+    fn stored(&self, name: &str) -> Result<String, String> {
+        let stored = match &self.store {
+            Some(store) if store.exists() => store.get(name)?,
+            _ => None,
+        };
+        stored.ok_or_else(|| {
+            format!(
+                "secret '{name}' is neither mapped in [secrets] nor stored with `mms secret set`"
+            )
+        })
+    }
+}
+
+fn run_command(command: &str, timeout: Duration) -> Result<String, String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("could not run `{command}`: {err}"))?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stdout_reader = thread::spawn(move || {
+        let mut buffer = String::new();
+        stdout.read_to_string(&mut buffer).map(|_| buffer)
+    });
+    let stderr_reader = thread::spawn(move || {
+        let mut buffer = String::new();
+        let _ = stderr.read_to_string(&mut buffer);
+        buffer
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("`{command}` timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(err) => return Err(format!("could not wait for `{command}`: {err}")),
+        }
+    };
+
+    let output = stdout_reader
+        .join()
+        .map_err(|_| format!("could not read output of `{command}`"))?
+        .map_err(|err| format!("could not read output of `{command}`: {err}"))?;
+    let errors = stderr_reader.join().unwrap_or_default();
+
+    if !status.success() {
+        let detail = errors.trim();
+        return Err(if detail.is_empty() {
+            format!("`{command}` failed with {status}")
+        } else {
+            format!("`{command}` failed with {status}: {detail}")
+        });
+    }
+    let value = output.trim_end_matches(['\n', '\r']).to_string();
+    if value.is_empty() {
+        return Err(format!("`{command}` printed nothing"));
+    }
+    Ok(value)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_named_commands_once_per_invocation() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("calls");
+        let command = format!("echo call >> {}; echo s3cret", log.display());
+        let providers = SecretProviders::new(
+            BTreeMap::from([("github".to_string(), command)]),
+            None,
+            DEFAULT_COMMAND_TIMEOUT,
+        );
+
+        let secret = Provider::Secret("github".into());
+        assert_eq!(providers.resolve(&secret).unwrap(), "s3cret");
+        assert_eq!(providers.resolve(&secret).unwrap(), "s3cret");
+        assert_eq!(std::fs::read_to_string(log).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn reports_failures_and_timeouts() {
+        let providers = SecretProviders::new(BTreeMap::new(), None, Duration::from_millis(200));
+
+        let failed = providers.resolve(&Provider::Command("echo nope >&2; exit 3".into()));
+        assert!(failed.unwrap_err().contains("nope"));
+
+        let slow = providers.resolve(&Provider::Command("sleep 5".into()));
+        assert!(slow.unwrap_err().contains("timed out"));
+
+        let unknown = providers.resolve(&Provider::Secret("missing".into()));
+        assert!(unknown.unwrap_err().contains("neither mapped"));
+    }
+}
@@ -0,0 +1,395 @@
+use super::model::McpServer;
+use crate::error::AppError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A `server.json` document as published to the official MCP registry.
+///
+/// Both the snake_case and the camelCase spellings of the registry schema are accepted.
+#[derive(Debug, Deserialize)]
+pub struct RegistryServer {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub packages: Vec<Package>,
+    #[serde(default)]
+    pub remotes: Vec<Remote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    #[serde(alias = "registryType", alias = "registry_name")]
+    pub registry_type: String,
+    #[serde(alias = "name")]
+    pub identifier: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default, alias = "runtimeArguments")]
+    pub runtime_arguments: Vec<Argument>,
+    #[serde(default, alias = "packageArguments")]
+    pub package_arguments: Vec<Argument>,
+    #[serde(default, alias = "environmentVariables")]
+    pub environment_variables: Vec<Input>,
+    #[serde(default)]
+    pub transport: Option<Transport>,
+}
+
+/// How a package is reached once running; packages without one speak stdio.
+#[derive(Debug, Deserialize)]
+pub struct Transport {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub headers: Vec<Input>,
+}
+
+/// A positional or `--named` argument passed to the runtime or the package.
+#[derive(Debug, Deserialize)]
+pub struct Argument {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default, alias = "valueHint")]
+    pub value_hint: Option<String>,
+}
+
+/// An environment variable or header the user has to supply.
+#[derive(Debug, Deserialize)]
+pub struct Input {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Remote {
+    #[serde(rename = "type")]
+    pub transport: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<Input>,
+}
+
+/// Read registry documents from a file or every `.json` file below a directory.
+///
+/// Files may hold a single `server.json` document or a registry listing with a `servers` array.
+pub fn load(path: &Path) -> Result<Vec<(String, McpServer)>, AppError> {
+    if !path.exists() {
+        return Err(AppError::missing_file(path));
+    }
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+
+    let mut entries: Vec<(String, McpServer)> = Vec::new();
+    for file in files {
+        let doc: Value = serde_json::from_str(&fs::read_to_string(&file)?).map_err(|err| {
+            AppError::config(format!("{} is not valid JSON: {err}", file.display()))
+        })?;
+        let documents = match doc.get("servers").and_then(Value::as_array) {
+            Some(servers) => {
+                servers.iter().map(|item| item.get("server").unwrap_or(item)).collect()
+            }
+            None => vec![&doc],
+        };
+        for document in documents {
+            let server: RegistryServer =
+                serde_json::from_value(document.clone()).map_err(|err| {
+                    AppError::config(format!(
+                        "{} is not a registry server.json: {err}",
+                        file.display()
+                    ))
+                })?;
+            let (name, entry) = convert(&server)
+                .map_err(|err| AppError::config(format!("{}: {err}", file.display())))?;
+            if entries.iter().any(|(existing, _)| *existing == name) {
+                return Err(AppError::config(format!(
+                    "{}: more than one registry server maps to the name '{name}'",
+                    file.display()
+                )));
+            }
+            entries.push((name, entry));
+        }
+    }
+    Ok(entries)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), AppError> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut children: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    children.sort();
+    for child in children {
+        if child.is_dir() {
+            collect_files(&child, files)?;
+        } else if child.extension().is_some_and(|ext| ext == "json") {
+            files.push(child);
+        }
+    }
+    Ok(())
+}
+
+/// Convert one registry server into a catalogue entry named after the last part of its name.
+///
+/// The first npm, PyPI or OCI package wins; servers without one use their first remote.
+pub fn convert(server: &RegistryServer) -> Result<(String, McpServer), String> {
+    let name = server.name.rsplit('/').next().unwrap_or(&server.name).to_lowercase();
+    if name.is_empty() {
+        return Err(format!("'{}' does not end in a usable server name", server.name));
+    }
+    let mut entry = if let Some(package) = server.packages.iter().find(|p| runner(p).is_some()) {
+        from_package(package)?
+    } else if let Some(remote) = server.remotes.first() {
+        from_remote(remote)?
+    } else {
+        return Err(format!("'{}' has no npm, pypi or oci package and no remote", server.name));
+    };
+    entry.description = server.description.clone();
+    Ok((name, entry))
+}
+
+fn runner(package: &Package) -> Option<&'static str> {
+    match package.registry_type.as_str() {
+        "npm" => Some("npx"),
+        "pypi" => Some("uvx"),
+        "oci" | "docker" => Some("docker"),
+        _ => None,
+    }
+}
+
+fn from_package(package: &Package) -> Result<McpServer, String> {
+    if let Some(transport) = package.transport.as_ref().filter(|t| t.kind != "stdio") {
+        let url = transport.url.as_deref().ok_or_else(|| {
+            format!("package transport '{}' of {} has no url", transport.kind, package.identifier)
+        })?;
+        return endpoint(&transport.kind, url, &transport.headers);
+    }
+    let command = runner(package).unwrap_or_default();
+    let identifier = &package.identifier;
+    let version = package.version.as_deref().filter(|v| !v.is_empty() && *v != "latest");
+
+    let mut args = Vec::new();
+    match command {
+        "npx" => args.push("-y".to_string()),
+        "docker" => args.extend(["run", "-i", "--rm"].map(String::from)),
+        _ => {}
+    }
+    push_arguments(&mut args, &package.runtime_arguments);
+    let qualified = match (command, version) {
+        ("npx", Some(version)) => format!("{identifier}@{version}"),
+        ("uvx", Some(version)) => format!("{identifier}=={version}"),
+        ("docker", Some(version)) if !identifier.contains([':', '@']) => {
+            format!("{identifier}:{version}")
+        }
+        _ => identifier.clone(),
+    };
+    if command == "docker" {
+        // Containers only see the variables passed through explicitly.
+        for variable in &package.environment_variables {
+            args.extend(["-e".to_string(), variable.name.clone()]);
+        }
+    }
+    args.push(qualified);
+    push_arguments(&mut args, &package.package_arguments);
+
+    Ok(McpServer {
+        server_type: Some("stdio".to_string()),
+        command: Some(command.to_string()),
+        args,
+        env: package
+            .environment_variables
+            .iter()
+            .map(|variable| (variable.name.clone(), input_value(variable)))
+            .collect(),
+        ..McpServer::default()
+    })
+}
+
+fn from_remote(remote: &Remote) -> Result<McpServer, String> {
+    endpoint(&remote.transport, &remote.url, &remote.headers)
+}
+
+/// A `url` entry for a remote or a package served over HTTP.
+fn endpoint(transport: &str, url: &str, headers: &[Input]) -> Result<McpServer, String> {
+    let server_type = match transport {
+        "streamable-http" | "http" => "http",
+        "sse" => "sse",
+        other => return Err(format!("remote transport '{other}' is not supported")),
+    };
+    Ok(McpServer {
+        server_type: Some(server_type.to_string()),
+        url: Some(placeholders(url)),
+        headers: headers.iter().map(|header| (header.name.clone(), input_value(header))).collect(),
+        ..McpServer::default()
+    })
+}
+
+fn push_arguments(args: &mut Vec<String>, arguments: &[Argument]) {
+    for argument in arguments {
+        let value =
+            argument.value.as_ref().or(argument.default.as_ref()).map(|v| placeholders(v)).or_else(
+                || argument.value_hint.as_ref().map(|h| format!("${{{}}}", variable_name(h))),
+            );
+        if argument.kind == "named" {
+            if let Some(name) = &argument.name {
+                args.push(name.clone());
+            }
+        }
+        args.extend(value);
+    }
+}
+
+/// A fixed value when the registry gives one, otherwise a `${NAME}` placeholder.
+fn input_value(input: &Input) -> String {
+    match (&input.value, &input.default) {
+        (Some(value), _) => placeholders(value),
+        (None, Some(default)) => {
+            format!("${{{}:-{}}}", variable_name(&input.name), escape(default))
+        }
+        (None, None) => format!("${{{}}}", variable_name(&input.name)),
+    }
+}
+
+/// Turn registry `{variable}` references into mms `${VARIABLE}` placeholders.
+fn placeholders(value: &str) -> String {
+    let value = escape(value);
+    let mut out = String::new();
+    let mut rest = value.as_str();
+    while let Some(start) = rest.find('{') {
+        let (before, after) = rest.split_at(start);
+        out.push_str(before);
+        let end = after.find('}');
+        let inner = end.map(|end| &after[1..end]);
+        match inner {
+            Some(inner)
+                if !before.ends_with('$')
+                    && !inner.is_empty()
+                    && inner.chars().all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c)) =>
+            {
+                out.push_str(&format!("${{{}}}", variable_name(inner)));
+                rest = &after[inner.len() + 2..];
+            }
+            _ => {
+                out.push('{');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn escape(value: &str) -> String {
+    value.replace('$', "$$")
+}
+
+fn variable_name(hint: &str) -> String {
+    hint.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> (String, McpServer) {
+        convert(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn packages_pick_their_runner() {
+        let (name, npm) = parse(
+            r#"{ "name": "io.github.acme/Weather", "description": "Forecasts",
+                 "packages": [{ "registry_type": "npm", "identifier": "@acme/weather",
+                   "version": "1.2.0",
+                   "runtime_arguments": [{ "type": "named", "name": "--node-options", "value": "--max-old-space-size=512" }],
+                   "package_arguments": [{ "type": "positional", "value_hint": "data-dir" },
+                                         { "type": "named", "name": "--port", "value_hint": "port" },
+                                         { "type": "named", "name": "--verbose" }],
+                   "environment_variables": [{ "name": "WEATHER_KEY" },
+                                             { "name": "UNITS", "default": "metric" }] }] }"#,
+        );
+        assert_eq!(name, "weather");
+        assert_eq!(npm.command.as_deref(), Some("npx"));
+        assert_eq!(
+            npm.args,
+            [
+                "-y",
+                "--node-options",
+                "--max-old-space-size=512",
+                "@acme/weather@1.2.0",
+                "${DATA_DIR}",
+                "--port",
+                "${PORT}",
+                "--verbose"
+            ]
+        );
+        assert_eq!(npm.env["WEATHER_KEY"], "${WEATHER_KEY}");
+        assert_eq!(npm.env["UNITS"], "${UNITS:-metric}");
+        assert_eq!(npm.description.as_deref(), Some("Forecasts"));
+
+        let (_, pypi) = parse(
+            r#"{ "name": "x/fetch", "packages": [{ "registryType": "pypi",
+                 "identifier": "mcp-fetch", "version": "0.3" }] }"#,
+        );
+        assert_eq!(pypi.command.as_deref(), Some("uvx"));
+        assert_eq!(pypi.args, ["mcp-fetch==0.3"]);
+
+        let (_, oci) = parse(
+            r#"{ "name": "x/db", "packages": [{ "registry_type": "oci", "identifier": "acme/db",
+                 "version": "2", "environment_variables": [{ "name": "DB_URL" }] }] }"#,
+        );
+        assert_eq!(oci.command.as_deref(), Some("docker"));
+        assert_eq!(oci.args, ["run", "-i", "--rm", "-e", "DB_URL", "acme/db:2"]);
+    }
+
+    #[test]
+    fn remotes_become_url_entries() {
+        let (_, remote) = parse(
+            r#"{ "name": "x/search", "remotes": [{ "type": "streamable-http",
+                 "url": "https://mcp.example.com/{tenant}/mcp",
+                 "headers": [{ "name": "Authorization", "value": "Bearer {api_key}" }] }] }"#,
+        );
+        assert_eq!(remote.server_type.as_deref(), Some("http"));
+        assert_eq!(remote.url.as_deref(), Some("https://mcp.example.com/${TENANT}/mcp"));
+        assert_eq!(remote.headers["Authorization"], "Bearer ${API_KEY}");
+    }
+
+    #[test]
+    fn http_packages_become_url_entries() {
+        let (_, served) = parse(
+            r#"{ "name": "x/docs", "packages": [{ "registry_type": "npm", "identifier": "docs-mcp",
+                 "transport": { "type": "streamable-http", "url": "http://localhost:{port}/mcp" } }] }"#,
+        );
+        assert_eq!(served.server_type.as_deref(), Some("http"));
+        assert_eq!(served.url.as_deref(), Some("http://localhost:${PORT}/mcp"));
+        assert!(served.command.is_none());
+
+        let (_, stdio) = parse(
+            r#"{ "name": "x/docs", "packages": [{ "registry_type": "npm", "identifier": "docs-mcp",
+                 "transport": { "type": "stdio" } }] }"#,
+        );
+        assert_eq!(stdio.command.as_deref(), Some("npx"));
+    }
+
+    #[test]
+    fn literal_dollars_are_escaped() {
+        assert_eq!(placeholders("a$b {x} {not valid}"), "a$$b ${X} {not valid}");
+    }
+}
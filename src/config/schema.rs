@@ -0,0 +1,70 @@
+use super::model::McpCatalogue;
+use super::validate::SERVER_TYPES;
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// Catalogue flavour a schema is generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// Project `.mcp.json`, which may reference global entries via `extends`.
+    Local,
+    /// User-wide `~/.mcp.json`.
+    Global,
+    /// Master catalogue (embedded or installed under `~/.config/mms`).
+    Master,
+}
+
+/// Build the JSON Schema for a catalogue kind from the Rust model types.
+pub fn catalogue_schema(kind: SchemaKind) -> Value {
+    let mut schema = schemars::schema_for!(McpCatalogue).to_value();
+
+    schema["title"] = json!(match kind {
+        SchemaKind::Local => "mms project catalogue (.mcp.json)",
+        SchemaKind::Global => "mms global catalogue (~/.mcp.json)",
+        SchemaKind::Master => "mms master catalogue",
+    });
+
+    if let Some(server) = schema.pointer_mut("/$defs/McpServer/properties") {
+        server["type"]["enum"] =
+            json!(SERVER_TYPES.iter().map(|t| json!(t)).chain([Value::Null]).collect::<Vec<_>>());
+        if kind != SchemaKind::Local {
+            // Only project catalogues may reference other entries.
+            server["extends"] = json!(false);
+        }
+    }
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MasterCatalogue;
+
+    #[test]
+    fn embedded_master_matches_schema() {
+        let schema = catalogue_schema(SchemaKind::Master);
+        let validator = jsonschema::validator_for(&schema).expect("schema should compile");
+        let master: Value = serde_json::from_str(MasterCatalogue::EMBEDDED_JSON).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&master).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "master catalogue violates schema: {errors:?}");
+    }
+
+    #[test]
+    fn schema_rejects_unknown_transport() {
+        let schema = catalogue_schema(SchemaKind::Local);
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let catalogue =
+            json!({ "mcpServers": { "a": { "type": "carrier-pigeon", "command": "x" } } });
+        assert!(!validator.is_valid(&catalogue));
+    }
+
+    #[test]
+    fn only_local_schema_allows_extends() {
+        let catalogue = json!({ "mcpServers": { "gh": { "extends": "github" } } });
+        let local = jsonschema::validator_for(&catalogue_schema(SchemaKind::Local)).unwrap();
+        let global = jsonschema::validator_for(&catalogue_schema(SchemaKind::Global)).unwrap();
+        assert!(local.is_valid(&catalogue));
+        assert!(!global.is_valid(&catalogue));
+    }
+}
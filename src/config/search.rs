@@ -0,0 +1,174 @@
+use super::model::{McpCatalogue, McpServer};
+use std::cmp::Ordering;
+
+/// Lowest similarity at which a word still counts as a fuzzy match for a query term.
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+/// The part of an entry a query term matched best.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Package,
+    Tag,
+    Description,
+}
+
+impl Field {
+    fn weight(self) -> f64 {
+        match self {
+            Self::Name => 1.0,
+            Self::Package => 0.9,
+            Self::Tag => 0.8,
+            Self::Description => 0.6,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Package => "package",
+            Self::Tag => "tag",
+            Self::Description => "description",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub name: String,
+    pub score: f64,
+    pub field: Field,
+    /// The text that matched, e.g. the package identifier.
+    pub matched: String,
+}
+
+/// Rank the servers of `catalogue` against `query`, best match first.
+///
+/// Every whitespace-separated term has to match the name, a tag, a package identifier in
+/// `args` or the description, exactly, as a substring or as a close misspelling.
+pub fn search(catalogue: &McpCatalogue, query: &str) -> Vec<Hit> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<Hit> = catalogue
+        .mcp_servers
+        .iter()
+        .filter_map(|(name, server)| {
+            let fields = fields(name, server);
+            let mut total = 0.0;
+            let mut best: Option<(f64, Field, &str)> = None;
+            for term in &terms {
+                let (score, field, text) = fields
+                    .iter()
+                    .map(|(field, text)| (similarity(term, text) * field.weight(), *field, *text))
+                    .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))?;
+                if score == 0.0 {
+                    return None;
+                }
+                total += score;
+                if best.is_none_or(|(top, _, _)| score > top) {
+                    best = Some((score, field, text));
+                }
+            }
+            let (_, field, matched) = best?;
+            Some(Hit {
+                name: name.clone(),
+                score: total / terms.len() as f64,
+                field,
+                matched: matched.to_string(),
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal).then_with(|| a.name.cmp(&b.name))
+    });
+    hits
+}
+
+fn fields<'a>(name: &'a str, server: &'a McpServer) -> Vec<(Field, &'a str)> {
+    let mut fields = vec![(Field::Name, name)];
+    fields.extend(server.args.iter().filter_map(|arg| package(arg)).map(|p| (Field::Package, p)));
+    if let Some(tags) = server.extra.get("tags").and_then(|tags| tags.as_array()) {
+        fields.extend(tags.iter().filter_map(|tag| tag.as_str()).map(|tag| (Field::Tag, tag)));
+    }
+    if let Some(description) = &server.description {
+        fields.push((Field::Description, description));
+    }
+    fields
+}
+
+/// The package identifier in an argument such as `@scope/name@latest` or `mcp-fetch==0.3`.
+fn package(arg: &str) -> Option<&str> {
+    if arg.starts_with('-') || (arg.contains(['=', ' ']) && !arg.contains("==")) {
+        return None;
+    }
+    if arg.contains("://") {
+        let last = arg.trim_end_matches('/').rsplit('/').next()?;
+        return Some(last.trim_end_matches(".git")).filter(|name| !name.is_empty());
+    }
+    // A leading `@` belongs to the npm scope, not the version.
+    let skip = arg.chars().next()?.len_utf8();
+    let end = arg[skip..].find(['@', '=', ':']).map_or(arg.len(), |index| index + skip);
+    Some(&arg[..end])
+}
+
+/// How well `term` matches `text`: 1 for equality, less for prefixes, substrings and typos.
+fn similarity(term: &str, text: &str) -> f64 {
+    let text = text.to_lowercase();
+    if text == term {
+        return 1.0;
+    }
+    if text.starts_with(term) {
+        return 0.95;
+    }
+    if text.contains(term) {
+        return 0.9;
+    }
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| strsim::jaro_winkler(term, word))
+        .filter(|score| *score >= FUZZY_THRESHOLD)
+        .fold(0.0, f64::max)
+        * 0.8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalogue() -> McpCatalogue {
+        serde_json::from_str(
+            r#"{ "mcpServers": {
+                "github": { "command": "npx", "args": ["-y", "@modelcontextprotocol/server-github"],
+                            "description": "Repositories, issues and pull requests" },
+                "context7": { "command": "npx", "args": ["-y", "@upstash/context7-mcp@latest"],
+                              "description": "Up-to-date library docs", "tags": ["docs"] },
+                "serena": { "command": "uvx",
+                            "args": ["--from", "git+https://github.com/oraios/serena", "serena"] },
+                "fetch": { "command": "uvx", "args": ["mcp-server-fetch==0.3"],
+                           "description": "Fetch web pages, including GitHub READMEs" }
+            } }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ranks_names_above_descriptions() {
+        let hits = search(&catalogue(), "github");
+        let names: Vec<&str> = hits.iter().map(|hit| hit.name.as_str()).collect();
+        assert_eq!(names, ["github", "fetch"]);
+        assert_eq!(hits[1].field, Field::Description);
+    }
+
+    #[test]
+    fn matches_packages_tags_and_typos() {
+        assert_eq!(search(&catalogue(), "upstash")[0].field, Field::Package);
+        assert_eq!(search(&catalogue(), "upstash")[0].matched, "@upstash/context7-mcp");
+        assert_eq!(search(&catalogue(), "docs")[0].name, "context7");
+        assert_eq!(search(&catalogue(), "githbu")[0].name, "github");
+        assert!(search(&catalogue(), "github kubernetes").is_empty());
+        assert_eq!(package("git+https://github.com/oraios/serena.git"), Some("serena"));
+        assert_eq!(package(""), None);
+    }
+}
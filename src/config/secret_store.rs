@@ -0,0 +1,237 @@
+use super::paths::MmsPaths;
+use super::sensitive;
+use super::settings::MmsSettings;
+use crate::error::AppError;
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming a key file that unlocks the store.
+pub const KEY_FILE_ENV: &str = "MMS_SECRET_KEY_FILE";
+/// Environment variable holding the store passphrase for non-interactive use.
+pub const PASSPHRASE_ENV: &str = "MMS_SECRET_PASSPHRASE";
+
+const FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Material used to derive the store encryption key.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    KeyFile(PathBuf),
+    /// A passphrase, or `None` to prompt on the terminal when the store is first opened.
+    Passphrase(Option<String>),
+}
+
+impl KeySource {
+    /// Pick the key source: explicit flag, `MMS_SECRET_KEY_FILE`, `[secret_store] key_file`,
+    /// `MMS_SECRET_PASSPHRASE`, then an interactive prompt.
+    pub fn detect(settings: &MmsSettings, key_file: Option<&Path>) -> Self {
+        if let Some(path) = key_file {
+            return Self::KeyFile(path.to_path_buf());
+        }
+        if let Some(path) = std::env::var_os(KEY_FILE_ENV) {
+            return Self::KeyFile(PathBuf::from(path));
+        }
+        if let Some(path) = &settings.secret_key_file {
+            return Self::KeyFile(path.clone());
+        }
+        Self::Passphrase(std::env::var(PASSPHRASE_ENV).ok())
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::KeyFile(_) => "key-file",
+            Self::Passphrase(_) => "passphrase",
+        }
+    }
+
+    fn material(&self, confirm: bool) -> Result<Vec<u8>, AppError> {
+        match self {
+            Self::KeyFile(path) => {
+                if !path.exists() {
+                    return Err(AppError::missing_file(path));
+                }
+                Ok(fs::read(path)?)
+            }
+            Self::Passphrase(Some(passphrase)) => Ok(passphrase.as_bytes().to_vec()),
+            Self::Passphrase(None) => {
+                if !std::io::stdin().is_terminal() {
+                    return Err(AppError::config(format!(
+                        "secret store is locked; set {PASSPHRASE_ENV} or {KEY_FILE_ENV}"
+                    )));
+                }
+                let passphrase = rpassword::prompt_password("mms secret store passphrase: ")?;
+                if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+                    return Err(AppError::config("passphrases do not match"));
+                }
+                if passphrase.is_empty() {
+                    return Err(AppError::config("passphrase must not be empty"));
+                }
+                Ok(passphrase.into_bytes())
+            }
+        }
+    }
+}
+
+/// On-disk envelope for the encrypted secrets map.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Encrypted name → value store kept in `~/.config/mms/secrets.enc`.
+#[derive(Debug)]
+pub struct SecretStore {
+    path: PathBuf,
+    key: KeySource,
+    material: OnceCell<Vec<u8>>,
+    unlocked: OnceCell<Result<BTreeMap<String, String>, String>>,
+}
+
+impl SecretStore {
+    pub fn new(path: PathBuf, key: KeySource) -> Self {
+        Self { path, key, material: OnceCell::new(), unlocked: OnceCell::new() }
+    }
+
+    /// Open the store at its standard location.
+    pub fn open(paths: &MmsPaths, settings: &MmsSettings, key_file: Option<&Path>) -> Self {
+        Self::new(paths.secret_store_path(), KeySource::detect(settings, key_file))
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Decrypt every secret. A missing store is empty.
+    pub fn read(&self) -> Result<BTreeMap<String, String>, AppError> {
+        if !self.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let envelope: Envelope = serde_json::from_str(&fs::read_to_string(&self.path)?)?;
+        if envelope.version != FORMAT_VERSION {
+            return Err(AppError::config(format!(
+                "{} uses unsupported format version {}",
+                self.path.display(),
+                envelope.version
+            )));
+        }
+        if envelope.kdf != self.key.kind() {
+            return Err(AppError::config(format!(
+                "{} is locked with a {}, but a {} was supplied",
+                self.path.display(),
+                envelope.kdf,
+                self.key.kind()
+            )));
+        }
+
+        let salt = decode(&envelope.salt)?;
+        let nonce = decode(&envelope.nonce)?;
+        let ciphertext = decode(&envelope.ciphertext)?;
+        let cipher = cipher(self.material(false)?, &salt)?;
+        let plaintext =
+            cipher.decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref()).map_err(|_| {
+                AppError::config(format!(
+                    "could not decrypt {}: wrong passphrase or key file",
+                    self.path.display()
+                ))
+            })?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    /// Encrypt and persist the full secrets map with owner-only permissions.
+    pub fn write(&self, secrets: &BTreeMap<String, String>) -> Result<(), AppError> {
+        let mut salt = [0u8; SALT_LEN];
+        chacha20poly1305::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let cipher = cipher(self.material(!self.exists())?, &salt)?;
+        let ciphertext = cipher
+            .encrypt(&nonce, serde_json::to_vec(secrets)?.as_ref())
+            .map_err(|_| AppError::config("failed to encrypt secret store"))?;
+
+        let envelope = Envelope {
+            version: FORMAT_VERSION,
+            kdf: self.key.kind().to_string(),
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let serialised = serde_json::to_string_pretty(&envelope)?;
+        sensitive::write_private(&self.path, &format!("{serialised}\n"))
+    }
+
+    /// Key material, asked for at most once so `set` does not prompt twice.
+    fn material(&self, confirm: bool) -> Result<&[u8], AppError> {
+        if self.material.get().is_none() {
+            let _ = self.material.set(self.key.material(confirm)?);
+        }
+        Ok(self.material.get().expect("material was just set"))
+    }
+
+    /// Look up one secret, unlocking the store at most once per invocation.
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        let secrets = self.unlocked.get_or_init(|| self.read().map_err(|err| err.to_string()));
+        secrets.as_ref().map(|secrets| secrets.get(name).cloned()).map_err(Clone::clone)
+    }
+
+    /// Look up one secret only if an earlier call already unlocked the store.
+    pub fn get_if_unlocked(&self, name: &str) -> Option<String> {
+        self.unlocked.get()?.as_ref().ok()?.get(name).cloned()
+    }
+}
+
+/// Whether `name` is usable as a secret name and in `${secret:NAME}`.
+pub fn is_secret_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn cipher(material: &[u8], salt: &[u8]) -> Result<XChaCha20Poly1305, AppError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(material, salt, &mut key)
+        .map_err(|err| AppError::config(format!("failed to derive secret store key: {err}")))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, AppError> {
+    BASE64.decode(value).map_err(|err| AppError::config(format!("corrupt secret store: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_with_passphrase_and_rejects_wrong_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.enc");
+        let store = SecretStore::new(path.clone(), KeySource::Passphrase(Some("hunter2".into())));
+        store.write(&BTreeMap::from([("github".to_string(), "ghp_x".to_string())])).unwrap();
+
+        assert!(!fs::read_to_string(&path).unwrap().contains("ghp_x"));
+        assert_eq!(store.read().unwrap()["github"], "ghp_x");
+
+        let wrong = SecretStore::new(path.clone(), KeySource::Passphrase(Some("nope".into())));
+        assert!(wrong.read().unwrap_err().to_string().contains("wrong passphrase"));
+
+        let key_file = dir.path().join("key");
+        fs::write(&key_file, "key material").unwrap();
+        let mismatched = SecretStore::new(path, KeySource::KeyFile(key_file));
+        assert!(mismatched.read().is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::error::AppError;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Write a file that holds resolved secrets, readable by the owner only.
+///
+/// The contents go to a `0600` sibling first and replace `path` by rename, so the secrets are
+/// never readable by others and a crash leaves either the old file or the new one. Warns on
+/// stderr, before anything is written, when the file would be picked up by git.
+pub fn write_private(path: &Path, contents: &str) -> Result<(), AppError> {
+    if let Some(exposure) = git_exposure(path) {
+        eprintln!("⚠️  {} {exposure}; writing resolved secrets to it", path.display());
+    }
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+    let _ = fs::remove_file(&temp);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options.open(&temp).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// Describe how git would see `path`, if it is tracked or could be committed.
+pub fn git_exposure(path: &Path) -> Option<&'static str> {
+    let dir = path.parent()?;
+    let file = path.file_name()?;
+    let git = |args: &[&std::ffi::OsStr]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    };
+
+    if !git(&["rev-parse".as_ref(), "--is-inside-work-tree".as_ref()]) {
+        return None;
+    }
+    if git(&["ls-files".as_ref(), "--error-unmatch".as_ref(), file]) {
+        return Some("is tracked by git");
+    }
+    if !git(&["check-ignore".as_ref(), "-q".as_ref(), file]) {
+        return Some("is inside a git repository and not ignored");
+    }
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn private_files_replace_the_old_copy_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,188 @@
+use super::layers::TeamLayer;
+use super::paths::MmsPaths;
+use super::providers::DEFAULT_COMMAND_TIMEOUT;
+use crate::error::AppError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+/// When `${VAR}` placeholders in catalogues are replaced by their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaceholderMode {
+    /// Resolve while writing `~/.mcp.json`, so every copy carries the values.
+    #[default]
+    Eager,
+    /// Keep placeholders in catalogues; resolve only when `sync` writes target configs.
+    Late,
+    /// Keep placeholders everywhere; `sync` rewrites them into each target's own syntax.
+    Native,
+}
+
+/// User preferences read from `~/.config/mms/config.toml`.
+#[derive(Debug, Clone)]
+pub struct MmsSettings {
+    pub placeholders: PlaceholderMode,
+    /// Seconds a `${cmd:...}` or `${secret:...}` provider may run.
+    pub command_timeout: Duration,
+    /// Named secret providers: `${secret:NAME}` runs the mapped shell command.
+    pub secrets: BTreeMap<String, String>,
+    /// Key file that unlocks the `mms secret` store instead of a passphrase.
+    pub secret_key_file: Option<PathBuf>,
+    /// Shared catalogues layered between the master and `~/.mcp.json`, lowest first.
+    pub teams: Vec<TeamLayer>,
+}
+
+impl Default for MmsSettings {
+    fn default() -> Self {
+        Self {
+            placeholders: PlaceholderMode::default(),
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            secrets: BTreeMap::new(),
+            secret_key_file: None,
+            teams: Vec::new(),
+        }
+    }
+}
+
+impl MmsSettings {
+    /// Load settings, falling back to defaults when the file does not exist.
+    pub fn load(paths: &MmsPaths) -> Result<Self, AppError> {
+        let path = paths.settings_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let doc: DocumentMut = fs::read_to_string(&path)?.parse()?;
+        let mut settings = Self::default();
+
+        if let Some(mode) = doc.get("placeholders").and_then(|t| t.get("resolve")) {
+            settings.placeholders = match mode.as_str() {
+                Some("eager") => PlaceholderMode::Eager,
+                Some("late") => PlaceholderMode::Late,
+                Some("native") => PlaceholderMode::Native,
+                _ => {
+                    return Err(AppError::config(format!(
+                        "{}: placeholders.resolve must be \"eager\", \"late\" or \"native\"",
+                        path.display()
+                    )))
+                }
+            };
+        }
+
+        if let Some(timeout) = doc.get("placeholders").and_then(|t| t.get("command_timeout")) {
+            let seconds = timeout.as_integer().filter(|s| *s > 0).ok_or_else(|| {
+                AppError::config(format!(
+                    "{}: placeholders.command_timeout must be a positive number of seconds",
+                    path.display()
+                ))
+            })?;
+            settings.command_timeout = Duration::from_secs(seconds as u64);
+        }
+
+        if let Some(secrets) = doc.get("secrets").and_then(|item| item.as_table_like()) {
+            for (name, command) in secrets.iter() {
+                let command = command.as_str().ok_or_else(|| {
+                    AppError::config(format!(
+                        "{}: secrets.{name} must be a command string",
+                        path.display()
+                    ))
+                })?;
+                settings.secrets.insert(name.to_string(), command.to_string());
+            }
+        }
+
+        if let Some(key_file) = doc.get("secret_store").and_then(|t| t.get("key_file")) {
+            let key_file = key_file.as_str().ok_or_else(|| {
+                AppError::config(format!(
+                    "{}: secret_store.key_file must be a path",
+                    path.display()
+                ))
+            })?;
+            settings.secret_key_file = Some(expand_home(paths, key_file));
+        }
+
+        if let Some(teams) = doc.get("teams") {
+            let teams = teams.as_array_of_tables().ok_or_else(|| {
+                AppError::config(format!(
+                    "{}: teams must be a list of [[teams]] tables",
+                    path.display()
+                ))
+            })?;
+            for team in teams.iter() {
+                let location = team.get("path").and_then(|p| p.as_str()).ok_or_else(|| {
+                    AppError::config(format!(
+                        "{}: every [[teams]] entry needs a path",
+                        path.display()
+                    ))
+                })?;
+                // Relative team paths are relative to the config directory.
+                let location = paths.config_dir().join(expand_home(paths, location));
+                let name = match team.get("name").map(|n| n.as_str()) {
+                    Some(Some(name)) => name.to_string(),
+                    Some(None) => {
+                        return Err(AppError::config(format!(
+                            "{}: teams.name must be a string",
+                            path.display()
+                        )))
+                    }
+                    None => location
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().trim_start_matches('.').to_string())
+                        .unwrap_or_else(|| "team".to_string()),
+                };
+                let text = |key: &str| team.get(key).and_then(|v| v.as_str()).map(str::to_string);
+                settings.teams.push(TeamLayer {
+                    name,
+                    path: location,
+                    url: text("url"),
+                    git_ref: text("ref"),
+                    allow_unsigned: team
+                        .get("allow_unsigned")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                });
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Append a `[[teams]]` entry to `config.toml`, keeping the rest of the file as it is.
+pub fn add_team(paths: &MmsPaths, team: &TeamLayer) -> Result<(), AppError> {
+    paths.ensure_config_dir()?;
+    let path = paths.settings_path();
+    let mut doc: DocumentMut =
+        if path.exists() { fs::read_to_string(&path)?.parse()? } else { DocumentMut::new() };
+    let teams = doc
+        .entry("teams")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| AppError::config(format!("{}: teams must be [[teams]]", path.display())))?;
+
+    let location = team.path.strip_prefix(paths.config_dir()).unwrap_or(&team.path);
+    let mut table = Table::new();
+    table["name"] = value(team.name.as_str());
+    table["path"] = value(location.display().to_string());
+    if let Some(url) = &team.url {
+        table["url"] = value(url.as_str());
+    }
+    if let Some(git_ref) = &team.git_ref {
+        table["ref"] = value(git_ref.as_str());
+    }
+    if team.allow_unsigned {
+        table["allow_unsigned"] = value(true);
+    }
+    teams.push(table);
+    fs::write(&path, doc.to_string())?;
+    Ok(())
+}
+
+/// Resolve a leading `~/` against the home directory.
+fn expand_home(paths: &MmsPaths, path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(relative) => paths.home().join(relative),
+        None => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,217 @@
+use super::paths::MmsPaths;
+use crate::error::AppError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::fs;
+use std::path::Path;
+
+/// Suffix of the detached minisign signature published next to a catalogue.
+pub const SIGNATURE_SUFFIX: &str = ".minisig";
+
+/// A publisher key from `~/.config/mms/trusted_keys`.
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl TrustedKey {
+    /// Parse a minisign public key (the base64 line of `minisign.pub`).
+    pub fn parse(line: &str) -> Result<Self, AppError> {
+        let bytes = STANDARD
+            .decode(line.trim())
+            .map_err(|err| AppError::config(format!("invalid public key: {err}")))?;
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            return Err(AppError::config("invalid public key: not a minisign Ed25519 key"));
+        }
+        let id = bytes[2..10].try_into().expect("slice is 8 bytes");
+        let key = VerifyingKey::from_bytes(bytes[10..].try_into().expect("slice is 32 bytes"))
+            .map_err(|err| AppError::config(format!("invalid public key: {err}")))?;
+        Ok(Self { id, key })
+    }
+
+    /// The key id as minisign prints it.
+    pub fn id(&self) -> String {
+        key_id(&self.id)
+    }
+}
+
+fn key_id(id: &[u8; 8]) -> String {
+    format!("{:016X}", u64::from_le_bytes(*id))
+}
+
+/// Read the trusted keys, one minisign public key per line; `#` and comment lines are skipped.
+pub fn trusted_keys(paths: &MmsPaths) -> Result<Vec<TrustedKey>, AppError> {
+    let path = paths.trusted_keys_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.starts_with('#') && !line.starts_with("untrusted comment:")
+        })
+        .map(|line| {
+            TrustedKey::parse(line)
+                .map_err(|err| AppError::config(format!("{}: {err}", path.display())))
+        })
+        .collect()
+}
+
+/// Verify a minisign signature over `contents`, returning the signer's key id.
+pub fn verify(contents: &[u8], signature: &str, keys: &[TrustedKey]) -> Result<String, String> {
+    let lines: Vec<&str> = signature.lines().map(str::trim).collect();
+    let (Some(encoded), Some(comment), Some(global)) = (lines.get(1), lines.get(2), lines.get(3))
+    else {
+        return Err("has a malformed signature file".to_string());
+    };
+    let decode = |text: &str| STANDARD.decode(text).map_err(|_| "has a malformed signature");
+    let bytes = decode(encoded)?;
+    if bytes.len() != 74 {
+        return Err("has a malformed signature".to_string());
+    }
+    let id: [u8; 8] = bytes[2..10].try_into().expect("slice is 8 bytes");
+    let Some(trusted) = keys.iter().find(|key| key.id == id) else {
+        return Err(format!("is signed by key {}, which is not trusted", key_id(&id)));
+    };
+    let signature = Signature::from_slice(&bytes[10..]).map_err(|_| "has a malformed signature")?;
+    let verified = match &bytes[..2] {
+        b"Ed" => trusted.key.verify_strict(contents, &signature),
+        b"ED" => trusted.key.verify_strict(&Blake2b512::digest(contents), &signature),
+        _ => return Err("uses an unsupported signature algorithm".to_string()),
+    };
+    if verified.is_err() {
+        return Err("does not match its signature".to_string());
+    }
+
+    let comment = comment.strip_prefix("trusted comment: ").unwrap_or(comment);
+    let global =
+        Signature::from_slice(&decode(global)?).map_err(|_| "has a malformed signature")?;
+    let signed = [&bytes[10..], comment.as_bytes()].concat();
+    if trusted.key.verify_strict(&signed, &global).is_err() {
+        return Err("has a tampered trusted comment".to_string());
+    }
+    Ok(trusted.id())
+}
+
+/// Accept `contents` only if its detached signature verifies against a trusted key.
+///
+/// With `allow_unsigned` a failure is reported loudly instead of refusing the catalogue.
+pub fn check(
+    paths: &MmsPaths,
+    source: &str,
+    contents: &[u8],
+    signature: Option<&str>,
+    allow_unsigned: bool,
+) -> Result<(), AppError> {
+    match outcome(paths, source, contents, signature)? {
+        Ok(key) => {
+            println!("🔏 {source} is signed by trusted key {key}");
+            Ok(())
+        }
+        Err(problem) => reject(paths, source, &problem, allow_unsigned),
+    }
+}
+
+/// Check a stored catalogue against the `.minisig` next to it, as every load does.
+///
+/// Unlike [`check`] a good signature is accepted quietly.
+pub fn check_stored(
+    paths: &MmsPaths,
+    path: &Path,
+    contents: &[u8],
+    allow_unsigned: bool,
+) -> Result<(), AppError> {
+    let source = path.display().to_string();
+    let signature = fs::read_to_string(format!("{source}{SIGNATURE_SUFFIX}")).ok();
+    match outcome(paths, &source, contents, signature.as_deref())? {
+        Ok(_) => Ok(()),
+        Err(problem) => reject(paths, &source, &problem, allow_unsigned),
+    }
+}
+
+fn outcome(
+    paths: &MmsPaths,
+    source: &str,
+    contents: &[u8],
+    signature: Option<&str>,
+) -> Result<Result<String, String>, AppError> {
+    let keys = trusted_keys(paths)?;
+    Ok(match signature {
+        None => Err(format!("has no signature ({source}{SIGNATURE_SUFFIX} not found)")),
+        Some(_) if keys.is_empty() => Err(format!(
+            "cannot be verified because {} lists no keys",
+            paths.trusted_keys_path().display()
+        )),
+        Some(signature) => verify(contents, signature, &keys),
+    })
+}
+
+fn reject(
+    paths: &MmsPaths,
+    source: &str,
+    problem: &str,
+    allow_unsigned: bool,
+) -> Result<(), AppError> {
+    if !allow_unsigned {
+        return Err(AppError::config(format!(
+            "{source} {problem}; refusing it. Add the publisher's minisign key to {} or pass \
+             --allow-unsigned",
+            paths.trusted_keys_path().display()
+        )));
+    }
+    eprintln!("⚠️  WARNING: {source} {problem}.");
+    eprintln!(
+        "⚠️  Accepting it because of --allow-unsigned. Its MCP servers run commands on this \
+         machine; make sure you trust where it came from."
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn sign(key: &SigningKey, contents: &[u8], prehash: bool) -> (String, String) {
+        let id = [7u8; 8];
+        let public = [b"Ed".as_slice(), &id, key.verifying_key().as_bytes()].concat();
+        let (alg, message) = if prehash {
+            (b"ED", Blake2b512::digest(contents).to_vec())
+        } else {
+            (b"Ed", contents.to_vec())
+        };
+        let signature = key.sign(&message).to_bytes();
+        let comment = "timestamp:0";
+        let global = key.sign(&[signature.as_slice(), comment.as_bytes()].concat()).to_bytes();
+        let file = format!(
+            "untrusted comment: test\n{}\ntrusted comment: {comment}\n{}\n",
+            STANDARD.encode([alg.as_slice(), &id, &signature].concat()),
+            STANDARD.encode(global)
+        );
+        (STANDARD.encode(public), file)
+    }
+
+    #[test]
+    fn verifies_minisign_signatures() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        for prehash in [false, true] {
+            let (public, signature) = sign(&key, b"catalogue", prehash);
+            let keys = vec![TrustedKey::parse(&public).unwrap()];
+            assert_eq!(verify(b"catalogue", &signature, &keys).unwrap(), keys[0].id());
+            assert_eq!(
+                verify(b"tampered", &signature, &keys).unwrap_err(),
+                "does not match its signature"
+            );
+        }
+
+        let (_, signature) = sign(&key, b"catalogue", true);
+        let (other, _) = sign(&SigningKey::from_bytes(&[2u8; 32]), b"", true);
+        let mut stranger = TrustedKey::parse(&other).unwrap();
+        stranger.id = [9u8; 8];
+        assert!(verify(b"catalogue", &signature, &[stranger]).unwrap_err().contains("not trusted"));
+    }
+}
@@ -0,0 +1,199 @@
+use super::layers::TeamLayer;
+use super::master::{self, ServerChange};
+use super::model::McpCatalogue;
+use super::paths::MmsPaths;
+use super::settings::{self, MmsSettings};
+use super::signature::{self, SIGNATURE_SUFFIX};
+use super::validate;
+use crate::error::AppError;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// What `pull` moved a subscription from and to.
+#[derive(Debug, Clone)]
+pub struct Pulled {
+    pub before: String,
+    pub after: String,
+    pub changes: Vec<ServerChange>,
+}
+
+/// Clone `source` into the sources directory and register it as a team layer.
+///
+/// The checkout is removed again if it has no valid, signed `.mcp.json`.
+pub fn subscribe(
+    paths: &MmsPaths,
+    settings: &MmsSettings,
+    source: &str,
+    git_ref: Option<&str>,
+    name: Option<&str>,
+    allow_unsigned: bool,
+) -> Result<(TeamLayer, McpCatalogue), AppError> {
+    let name = name.map(str::to_string).unwrap_or_else(|| default_name(source));
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(AppError::config(format!(
+            "'{name}' is not a usable catalogue name; pass --name"
+        )));
+    }
+    if settings.teams.iter().any(|team| team.name == name) {
+        return Err(AppError::config(format!(
+            "a team catalogue named '{name}' is already configured; pass --name"
+        )));
+    }
+    let dir = paths.sources_dir().join(&name);
+    if dir.exists() {
+        return Err(AppError::config(format!("{} already exists", dir.display())));
+    }
+
+    fs::create_dir_all(paths.sources_dir())?;
+    // `--` keeps a source such as `--upload-pack=...` from being read as an option.
+    git(None, &["clone", "--quiet", "--", source, &dir.display().to_string()])?;
+    let team = TeamLayer {
+        name,
+        path: dir.clone(),
+        url: Some(source.to_string()),
+        git_ref: git_ref.map(str::to_string),
+        allow_unsigned,
+    };
+    match checkout(&dir, git_ref).and_then(|_| check(paths, &team, allow_unsigned)) {
+        Ok(catalogue) => {
+            settings::add_team(paths, &team)?;
+            Ok((team, catalogue))
+        }
+        Err(err) => {
+            if let Err(cleanup) = fs::remove_dir_all(&dir) {
+                eprintln!("⚠️  Could not remove {}: {cleanup}", dir.display());
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Fetch a subscription and move it to the latest revision of its ref.
+///
+/// If the new revision's catalogue is invalid or unsigned the checkout stays where it was.
+pub fn pull(paths: &MmsPaths, team: &TeamLayer, allow_unsigned: bool) -> Result<Pulled, AppError> {
+    let dir = team.path.as_path();
+    let previous = team.load(paths).unwrap_or_default();
+    let before = git(Some(dir), &["rev-parse", "--short", "HEAD"])?;
+    git(Some(dir), &["fetch", "--quiet", "--tags", "origin"])?;
+    if team.git_ref.is_none() {
+        // `fetch` leaves origin/HEAD alone; follow a changed default branch too.
+        let _ = git(Some(dir), &["remote", "set-head", "origin", "--auto"]);
+    }
+    checkout(dir, team.git_ref.as_deref())?;
+    match check(paths, team, allow_unsigned || team.allow_unsigned) {
+        Ok(current) => {
+            if allow_unsigned && !team.allow_unsigned {
+                eprintln!(
+                    "ℹ️  --allow-unsigned applies to this pull only; loading team:{} and later \
+                     pulls check its signature again",
+                    team.name
+                );
+            }
+            Ok(Pulled {
+                before,
+                after: git(Some(dir), &["rev-parse", "--short", "HEAD"])?,
+                changes: master::diff(&previous, &current),
+            })
+        }
+        Err(err) => match git(Some(dir), &["checkout", "--quiet", "--detach", &before]) {
+            Ok(_) => Err(AppError::config(format!("{err}; staying at {before}"))),
+            Err(restore) => Err(AppError::config(format!(
+                "{err}; could not return to {before} either: {restore}"
+            ))),
+        },
+    }
+}
+
+/// The commit a team checkout is at, if it is a git checkout.
+pub fn revision(dir: &Path) -> Option<String> {
+    git(Some(dir), &["rev-parse", "--short", "HEAD"]).ok()
+}
+
+/// Layer name derived from the last component of a URL or path, without `.git`.
+fn default_name(source: &str) -> String {
+    let last = source.trim_end_matches('/').rsplit(['/', ':', '\\']).next().unwrap_or(source);
+    last.trim_end_matches(".git").to_string()
+}
+
+/// Detach the checkout at `git_ref`, preferring the remote branch of that name.
+fn checkout(dir: &Path, git_ref: Option<&str>) -> Result<(), AppError> {
+    let target = match git_ref {
+        Some(name) => {
+            let branch = format!("refs/remotes/origin/{name}");
+            if git(Some(dir), &["rev-parse", "--verify", "--quiet", &branch]).is_ok() {
+                format!("origin/{name}")
+            } else {
+                name.to_string()
+            }
+        }
+        None => "origin/HEAD".to_string(),
+    };
+    // Resolve first: `checkout` has no reliable way to mark where its options end.
+    let commit = git(
+        Some(dir),
+        &["rev-parse", "--verify", "--quiet", "--end-of-options", &format!("{target}^{{commit}}")],
+    )
+    .map_err(|_| AppError::config(format!("'{target}' is not a branch, tag or commit")))?;
+    git(Some(dir), &["checkout", "--quiet", "--detach", &commit]).map(|_| ())
+}
+
+/// Verify the checkout's catalogue signature, validate the catalogue and load it.
+fn check(
+    paths: &MmsPaths,
+    team: &TeamLayer,
+    allow_unsigned: bool,
+) -> Result<McpCatalogue, AppError> {
+    let path = team.catalogue_path();
+    if !path.exists() {
+        return Err(AppError::config(format!(
+            "{} has no .mcp.json at its root",
+            team.url.as_deref().unwrap_or(&team.path.display().to_string())
+        )));
+    }
+    let source = path.display().to_string();
+    let contents = fs::read_to_string(&path)?;
+    let signature = fs::read_to_string(format!("{source}{SIGNATURE_SUFFIX}")).ok();
+    signature::check(paths, &source, contents.as_bytes(), signature.as_deref(), allow_unsigned)?;
+    let diagnostics = validate::validate_str(&path, &contents);
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    if validate::has_errors(&diagnostics) {
+        return Err(AppError::config(format!("{} failed validation", path.display())));
+    }
+    TeamLayer::parse(&path, &contents)
+}
+
+/// Run `git`, optionally inside `dir`, and return its trimmed stdout.
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, AppError> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|err| AppError::config(format!("could not run git: {err}")))?;
+    if !output.status.success() {
+        return Err(AppError::config(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_come_from_the_repository() {
+        assert_eq!(default_name("https://github.com/acme/mcp-catalogue.git"), "mcp-catalogue");
+        assert_eq!(default_name("git@github.com:acme/servers.git"), "servers");
+        assert_eq!(default_name("/srv/git/team/"), "team");
+    }
+}
@@ -0,0 +1,634 @@
+use super::model::CatalogueMeta;
+use super::placeholder;
+use crate::error::AppError;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Keys understood on a server entry; anything else is preserved in `extra`.
+const SERVER_KEYS: &[&str] = &[
+    "extends",
+    "type",
+    "command",
+    "args",
+    "env",
+    "description",
+    "timeout",
+    "cwd",
+    "url",
+    "headers",
+];
+
+/// Top-level keys understood on a catalogue.
+const CATALOGUE_KEYS: &[&str] = &["mcpServers", "mms"];
+
+/// Transport types accepted in the `type` field.
+pub const SERVER_TYPES: &[&str] = &["stdio", "sse", "http", "streamable-http"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single validation finding anchored to a position in the catalogue file.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub rule: &'static str,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}:{}: {level}[{}]: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.rule,
+            self.message
+        )?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  help: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether any diagnostic in the list should block writes.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// Validate a catalogue file on disk.
+pub fn validate_file(path: &Path) -> Result<Vec<Diagnostic>, AppError> {
+    if !path.exists() {
+        return Err(AppError::missing_file(path));
+    }
+    let contents = fs::read_to_string(path)?;
+    Ok(validate_str(path, &contents))
+}
+
+/// Validate catalogue JSON, attributing diagnostics to `path`.
+pub fn validate_str(path: &Path, contents: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker { path, contents, diagnostics: Vec::new() };
+
+    match serde_json::from_str::<Value>(contents) {
+        Ok(root) => checker.check_catalogue(&root),
+        Err(err) => checker.diagnostics.push(Diagnostic {
+            path: path.to_path_buf(),
+            line: err.line(),
+            column: err.column(),
+            severity: Severity::Error,
+            rule: "syntax",
+            message: format!("invalid JSON: {err}"),
+            suggestion: None,
+        }),
+    }
+    checker.diagnostics.sort_by_key(|d| (d.line, d.column));
+    checker.diagnostics
+}
+
+/// What a diagnostic points at: a value, or the key that names it, by its path from the root.
+#[derive(Clone, Copy)]
+enum At<'p> {
+    Value(&'p [&'p str]),
+    Key(&'p [&'p str]),
+}
+
+struct Checker<'a> {
+    path: &'a Path,
+    contents: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn push(
+        &mut self,
+        at: At,
+        severity: Severity,
+        rule: &'static str,
+        message: String,
+        suggestion: Option<String>,
+    ) {
+        let (line, column) = locate::position(self.contents, at);
+        self.diagnostics.push(Diagnostic {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            severity,
+            rule,
+            message,
+            suggestion,
+        });
+    }
+
+    fn check_catalogue(&mut self, root: &Value) {
+        let Some(entries) = root.as_object() else {
+            self.push(
+                At::Value(&[]),
+                Severity::Error,
+                "root-type",
+                "catalogue root must be a JSON object".to_string(),
+                Some("wrap the servers in {\"mcpServers\": { ... }}".to_string()),
+            );
+            return;
+        };
+
+        for (key, value) in entries {
+            match key.as_str() {
+                "mcpServers" => self.check_servers(value),
+                "mms" => self.check_meta(value),
+                _ => self.check_unknown_key(&[key], CATALOGUE_KEYS, "catalogue"),
+            }
+        }
+    }
+
+    fn check_meta(&mut self, meta: &Value) {
+        if let Err(err) = serde_json::from_value::<CatalogueMeta>(meta.clone()) {
+            self.push(
+                At::Value(&["mms"]),
+                Severity::Error,
+                "field-type",
+                format!("`mms` is not valid catalogue metadata: {err}"),
+                Some("`mms` must be an object such as {\"schemaVersion\": 1}".to_string()),
+            );
+        }
+    }
+
+    fn check_servers(&mut self, servers: &Value) {
+        let Some(entries) = servers.as_object() else {
+            self.push(
+                At::Value(&["mcpServers"]),
+                Severity::Error,
+                "field-type",
+                "`mcpServers` must be an object keyed by server name".to_string(),
+                None,
+            );
+            return;
+        };
+        for (name, server) in entries {
+            self.check_server(name, server);
+        }
+    }
+
+    fn check_server(&mut self, name: &str, server: &Value) {
+        let at = ["mcpServers", name];
+        let Some(fields) = server.as_object() else {
+            self.push(
+                At::Value(&at),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}' must be an object"),
+                None,
+            );
+            return;
+        };
+
+        let has = |field: &str| fields.contains_key(field);
+        if !has("command") && !has("url") && !has("extends") {
+            let transport = fields.get("type").and_then(Value::as_str);
+            if matches!(transport, None | Some("stdio")) {
+                self.push(
+                    At::Key(&at),
+                    Severity::Error,
+                    "missing-command",
+                    format!("server '{name}' has no `command`"),
+                    Some("add a `command`, a `url`, or `extends` a global server".to_string()),
+                );
+            }
+        }
+
+        for (key, value) in fields {
+            let at = ["mcpServers", name, key];
+            match key.as_str() {
+                "type" => self.check_type(name, value),
+                "command" => self.check_command(name, value),
+                "args" => self.check_args(name, value),
+                "env" => self.check_env(name, value),
+                "timeout" => {
+                    if !value.is_u64() {
+                        self.push(
+                            At::Value(&at),
+                            Severity::Error,
+                            "field-type",
+                            format!("server '{name}': `timeout` must be a non-negative integer"),
+                            None,
+                        );
+                    }
+                }
+                "cwd" | "url" if value.is_string() => {
+                    self.check_placeholders(name, &format!("`{key}`"), &at, value);
+                }
+                "extends" | "description" | "cwd" | "url" => {
+                    if !value.is_string() {
+                        self.push(
+                            At::Value(&at),
+                            Severity::Error,
+                            "field-type",
+                            format!("server '{name}': `{key}` must be a string"),
+                            None,
+                        );
+                    }
+                }
+                "headers" => self.check_headers(name, value),
+                _ => self.check_unknown_key(&at, SERVER_KEYS, &format!("server '{name}'")),
+            }
+        }
+    }
+
+    fn check_type(&mut self, name: &str, value: &Value) {
+        let at = ["mcpServers", name, "type"];
+        let Some(transport) = value.as_str() else {
+            self.push(
+                At::Value(&at),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}': `type` must be a string"),
+                None,
+            );
+            return;
+        };
+        if !SERVER_TYPES.contains(&transport) {
+            let suggestion = closest(transport, SERVER_TYPES)
+                .map(|candidate| format!("did you mean \"{candidate}\"?"))
+                .or_else(|| Some(format!("use one of: {}", SERVER_TYPES.join(", "))));
+            self.push(
+                At::Value(&at),
+                Severity::Error,
+                "unknown-type",
+                format!("server '{name}' has unknown type \"{transport}\""),
+                suggestion,
+            );
+        }
+    }
+
+    fn check_command(&mut self, name: &str, value: &Value) {
+        let at = ["mcpServers", name, "command"];
+        match value.as_str() {
+            Some(command) if command.trim().is_empty() => self.push(
+                At::Value(&at),
+                Severity::Error,
+                "empty-command",
+                format!("server '{name}' has an empty `command`"),
+                Some("set the executable to launch, e.g. \"npx\"".to_string()),
+            ),
+            Some(_) => self.check_placeholders(name, "`command`", &at, value),
+            None => self.push(
+                At::Value(&at),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}': `command` must be a string"),
+                None,
+            ),
+        }
+    }
+
+    fn check_args(&mut self, name: &str, value: &Value) {
+        let Some(items) = value.as_array() else {
+            self.push(
+                At::Value(&["mcpServers", name, "args"]),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}': `args` must be an array of strings"),
+                None,
+            );
+            return;
+        };
+        for (index, item) in items.iter().enumerate() {
+            let position = index.to_string();
+            let at = ["mcpServers", name, "args", &position];
+            match item.as_str() {
+                Some("") => self.push(
+                    At::Value(&at),
+                    Severity::Error,
+                    "empty-arg",
+                    format!("server '{name}': argument {index} is an empty string"),
+                    Some("remove the empty argument".to_string()),
+                ),
+                Some(_) => self.check_placeholders(name, &format!("argument {index}"), &at, item),
+                None => self.push(
+                    At::Value(&at),
+                    Severity::Error,
+                    "field-type",
+                    format!("server '{name}': argument {index} must be a string"),
+                    None,
+                ),
+            }
+        }
+    }
+
+    fn check_env(&mut self, name: &str, value: &Value) {
+        let Some(entries) = value.as_object() else {
+            self.push(
+                At::Value(&["mcpServers", name, "env"]),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}': `env` must be an object of strings"),
+                None,
+            );
+            return;
+        };
+        for (key, item) in entries {
+            let at = ["mcpServers", name, "env", key];
+            if !item.is_string() {
+                self.push(
+                    At::Value(&at),
+                    Severity::Error,
+                    "field-type",
+                    format!("server '{name}': env var '{key}' must be a string"),
+                    None,
+                );
+                continue;
+            }
+            self.check_placeholders(name, &format!("env var '{key}'"), &at, item);
+        }
+    }
+
+    fn check_headers(&mut self, name: &str, value: &Value) {
+        let entries = value.as_object();
+        if !entries.is_some_and(|entries| entries.values().all(Value::is_string)) {
+            self.push(
+                At::Value(&["mcpServers", name, "headers"]),
+                Severity::Error,
+                "field-type",
+                format!("server '{name}': `headers` must be an object of strings"),
+                None,
+            );
+        }
+        for (header, item) in entries.into_iter().flat_map(Map::iter) {
+            if item.is_string() {
+                let at = ["mcpServers", name, "headers", header];
+                self.check_placeholders(name, &format!("header '{header}'"), &at, item);
+            }
+        }
+    }
+
+    fn check_placeholders(&mut self, name: &str, field: &str, at: &[&str], value: &Value) {
+        let Some(text) = value.as_str() else {
+            return;
+        };
+        if let Err(problem) = placeholder::parse(text) {
+            self.push(
+                At::Value(at),
+                Severity::Error,
+                "placeholder",
+                format!("server '{name}': {field} has an invalid placeholder: {problem}"),
+                Some(
+                    "use ${VAR}, ${VAR:-default} or ${VAR:?message}; write $$ for a literal $"
+                        .into(),
+                ),
+            );
+        }
+    }
+
+    fn check_unknown_key(&mut self, at: &[&str], known: &[&str], owner: &str) {
+        let key = at.last().copied().unwrap_or_default();
+        if let Some(candidate) = closest(key, known) {
+            self.push(
+                At::Key(at),
+                Severity::Warning,
+                "unknown-key",
+                format!("{owner} has unknown key \"{key}\"; it will be kept as an extra field"),
+                Some(format!("did you mean \"{candidate}\"?")),
+            );
+        }
+    }
+}
+
+/// Pick the known name closest to `input`, if it is a plausible misspelling.
+fn closest<'a>(input: &str, known: &[&'a str]) -> Option<&'a str> {
+    let lowered = input.to_ascii_lowercase();
+    known
+        .iter()
+        .map(|candidate| {
+            (candidate, strsim::levenshtein(&lowered, &candidate.to_ascii_lowercase()))
+        })
+        .filter(|(candidate, distance)| *distance <= 2.max(candidate.len() / 4))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| *candidate)
+}
+
+/// Positions of values in the source, taken from serde_json's own error locations.
+///
+/// Deserialising with [`Seek`] skips everything up to the wanted value and fails there, so
+/// the error carries its line and column.
+mod locate {
+    use super::At;
+    use serde::de::{
+        self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor,
+    };
+    use std::fmt;
+
+    const FOUND: &str = "found";
+    const CONTAINER: &str = "container";
+
+    /// Where a scalar value starts, or the key naming an object, array or `At::Key`.
+    pub fn position(contents: &str, at: At) -> (usize, usize) {
+        let (path, key) = match at {
+            At::Value(path) => (path, false),
+            At::Key(path) => (path, true),
+        };
+        match seek(contents, path, key) {
+            // serde_json locates objects and arrays after their whitespace; use the key instead.
+            Some(Err(())) => {
+                let named = path.iter().rposition(|part| part.parse::<usize>().is_err());
+                match named {
+                    Some(last) => seek(contents, &path[..=last], true),
+                    None => None,
+                }
+                .and_then(Result::ok)
+                .unwrap_or((1, 1))
+            }
+            Some(Ok(position)) => position,
+            None => (1, 1),
+        }
+    }
+
+    /// `Ok(position)` when found, `Err(())` when the value is an object or array.
+    fn seek(contents: &str, path: &[&str], key: bool) -> Option<Result<(usize, usize), ()>> {
+        let mut deserializer = serde_json::Deserializer::from_str(contents);
+        let err = Seek { path, key }.deserialize(&mut deserializer).err()?;
+        match err.to_string() {
+            message if message.starts_with(FOUND) => {
+                Some(Ok(token_start(contents, err.line(), err.column())))
+            }
+            message if message.starts_with(CONTAINER) => Some(Err(())),
+            _ => None,
+        }
+    }
+
+    /// serde_json reports where a string, number or literal ends; step back to where it starts.
+    fn token_start(contents: &str, line: usize, column: usize) -> (usize, usize) {
+        let Some(text) = contents.lines().nth(line.saturating_sub(1)) else {
+            return (line, column);
+        };
+        let bytes = &text.as_bytes()[..column.min(text.len())];
+        let Some(mut start) = bytes.len().checked_sub(1) else {
+            return (line, column);
+        };
+        if bytes[start] == b'"' {
+            let escaped =
+                |at: usize| bytes[..at].iter().rev().take_while(|b| **b == b'\\').count() % 2;
+            start = (0..start).rev().find(|&at| bytes[at] == b'"' && escaped(at) == 0).unwrap_or(0);
+        } else {
+            while start > 0 && !b" \t:,[{".contains(&bytes[start - 1]) {
+                start -= 1;
+            }
+        }
+        (line, start + 1)
+    }
+
+    struct Seek<'p> {
+        path: &'p [&'p str],
+        key: bool,
+    }
+
+    impl Seek<'_> {
+        fn scalar<E: de::Error>(self) -> Result<(), E> {
+            match self.path.is_empty() {
+                true => Err(E::custom(FOUND)),
+                false => Ok(()),
+            }
+        }
+    }
+
+    impl<'de> DeserializeSeed<'de> for Seek<'_> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for Seek<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("any JSON value")
+        }
+
+        fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+            self.scalar()
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+            let Some((wanted, rest)) = self.path.split_first() else {
+                return Err(de::Error::custom(CONTAINER));
+            };
+            while let Some(name) = map.next_key::<String>()? {
+                if name != *wanted {
+                    map.next_value::<IgnoredAny>()?;
+                } else if rest.is_empty() && self.key {
+                    return Err(de::Error::custom(FOUND));
+                } else {
+                    return map.next_value_seed(Seek { path: rest, key: self.key });
+                }
+            }
+            Ok(())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+            let Some((wanted, rest)) = self.path.split_first() else {
+                return Err(de::Error::custom(CONTAINER));
+            };
+            let Ok(wanted) = wanted.parse::<usize>() else { return Ok(()) };
+            for _ in 0..wanted {
+                if seq.next_element::<IgnoredAny>()?.is_none() {
+                    return Ok(());
+                }
+            }
+            seq.next_element_seed(Seek { path: rest, key: self.key }).map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(contents: &str) -> Vec<(&'static str, usize, usize)> {
+        validate_str(Path::new(".mcp.json"), contents)
+            .into_iter()
+            .map(|d| (d.rule, d.line, d.column))
+            .collect()
+    }
+
+    #[test]
+    fn reports_semantic_problems_with_positions() {
+        let contents = r#"{
+  "mcpServers": {
+    "a": { "command": " ", "args": ["x", ""], "type": "stdoi" },
+    "b": { "command": "run", "env": { "TOKEN": "${}" }, "comand": "typo" }
+  }
+}"#;
+        let found = rules(contents);
+        assert!(found.contains(&("empty-command", 3, 23)));
+        assert!(found.contains(&("empty-arg", 3, 42)));
+        assert!(found.contains(&("unknown-type", 3, 55)));
+        assert!(found.contains(&("placeholder", 4, 48)));
+        assert!(found.contains(&("unknown-key", 4, 57)));
+    }
+
+    #[test]
+    fn objects_are_located_by_their_key() {
+        let contents = "{\n  \"mcpServers\": {\n    \"a\": {\n      \"env\": []\n    }\n  }\n}";
+        assert_eq!(rules(contents), [("missing-command", 3, 5), ("field-type", 4, 7)]);
+    }
+
+    #[test]
+    fn rejects_malformed_mms_meta() {
+        let found = validate_str(Path::new(".mcp.json"), "{\"mcpServers\": {}, \"mms\": 1}");
+        assert_eq!(found.len(), 1, "{found:?}");
+        assert_eq!((found[0].rule, found[0].line, found[0].column), ("field-type", 1, 27));
+        assert!(found[0].message.contains("`mms` is not valid catalogue metadata"));
+        let wrong_version = r#"{"mcpServers": {}, "mms": {"schemaVersion": "one"}}"#;
+        assert_eq!(rules(wrong_version), [("field-type", 1, 20)]);
+        assert!(rules(r#"{"mcpServers": {}, "mms": {"schemaVersion": 1}}"#).is_empty());
+    }
+
+    #[test]
+    fn syntax_errors_carry_serde_location() {
+        let found = rules("{\n  \"mcpServers\": {,}\n}");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "syntax");
+        assert_eq!(found[0].1, 2);
+    }
+
+    #[test]
+    fn embedded_master_is_clean() {
+        let found = rules(super::super::MasterCatalogue::EMBEDDED_JSON);
+        assert!(found.is_empty(), "unexpected diagnostics: {found:?}");
+    }
+}
@@ -0,0 +1,309 @@
+use super::paths::MmsPaths;
+use super::placeholder::VariableSource;
+use super::providers::{Provider, SecretProviders};
+use super::secret_store::SecretStore;
+use super::settings::MmsSettings;
+use crate::error::AppError;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the conventional per-project env file.
+pub const PROJECT_ENV_FILE: &str = ".mms.env";
+
+/// Where a variable value was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarOrigin {
+    Prompt,
+    Process,
+    File(PathBuf),
+    SecretStore,
+}
+
+impl fmt::Display for VarOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prompt => write!(f, "prompt"),
+            Self::Process => write!(f, "process environment"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::SecretStore => write!(f, "secret store"),
+        }
+    }
+}
+
+/// Layered placeholder values, consulted in order of precedence.
+///
+/// From highest to lowest: values entered at a prompt during this run, `--env-file` arguments
+/// (later files win), the process environment, the project `.mms.env`, `~/.config/mms/env`, then
+/// the `mms secret` store.
+#[derive(Debug)]
+pub struct Variables {
+    entered: RefCell<BTreeMap<String, String>>,
+    layers: Vec<(VarOrigin, Option<BTreeMap<String, String>>)>,
+    providers: SecretProviders,
+    looked_up: RefCell<BTreeSet<String>>,
+}
+
+impl Variables {
+    /// Assemble the standard layers for a workspace.
+    pub fn load(
+        paths: &MmsPaths,
+        settings: &MmsSettings,
+        workspace: &Path,
+        env_files: &[PathBuf],
+    ) -> Result<Self, AppError> {
+        let mut layers = Vec::new();
+        for path in env_files.iter().rev() {
+            if !path.exists() {
+                return Err(AppError::missing_file(path));
+            }
+            layers.push((VarOrigin::File(path.clone()), Some(parse_env_file(path)?)));
+        }
+        layers.push((VarOrigin::Process, None));
+        for path in [workspace.join(PROJECT_ENV_FILE), paths.user_env_path()] {
+            if path.exists() {
+                let values = parse_env_file(&path)?;
+                layers.push((VarOrigin::File(path), Some(values)));
+            }
+        }
+        let store = SecretStore::open(paths, settings, None);
+        let providers =
+            SecretProviders::new(settings.secrets.clone(), Some(store), settings.command_timeout);
+        Ok(Self { entered: RefCell::default(), layers, providers, looked_up: RefCell::default() })
+    }
+
+    /// Find a value together with the layer it came from.
+    pub fn resolve(&self, name: &str) -> Option<(String, VarOrigin)> {
+        self.resolve_unlocked(name).or_else(|| {
+            self.providers.store_value(name).map(|value| (value, VarOrigin::SecretStore))
+        })
+    }
+
+    /// Like [`Self::resolve`], but without unlocking the secret store.
+    pub fn resolve_unlocked(&self, name: &str) -> Option<(String, VarOrigin)> {
+        if let Some(value) = self.entered.borrow().get(name) {
+            return Some((value.clone(), VarOrigin::Prompt));
+        }
+        self.layers.iter().find_map(|(origin, values)| {
+            let value = match values {
+                Some(values) => values.get(name).cloned(),
+                None => std::env::var(name).ok(),
+            };
+            value.map(|value| (value, origin.clone()))
+        })
+    }
+
+    /// Like [`Self::resolve`], but reads the secret store only if it is already unlocked.
+    fn resolve_if_unlocked(&self, name: &str) -> Option<(String, VarOrigin)> {
+        self.resolve_unlocked(name).or_else(|| {
+            self.providers.unlocked_store_value(name).map(|value| (value, VarOrigin::SecretStore))
+        })
+    }
+
+    /// Whether a secret store exists that [`Self::resolve`] may unlock.
+    pub fn has_secret_store(&self) -> bool {
+        self.providers.has_store()
+    }
+
+    /// Use `value` for `name` for the rest of this run, ahead of every other layer.
+    pub fn remember(&self, name: &str, value: String) {
+        self.entered.borrow_mut().insert(name.to_string(), value);
+    }
+
+    /// Describe the origin of every variable consulted so far, without revealing values.
+    pub fn lookup_report(&self) -> Vec<String> {
+        self.looked_up
+            .borrow()
+            .iter()
+            .map(|name| match self.resolve_if_unlocked(name) {
+                Some((_, origin)) => format!("{name} ← {origin}"),
+                None => format!("{name} ← (not set)"),
+            })
+            .collect()
+    }
+
+    /// Values substituted or entered so far in this run, for redacting output.
+    pub fn known_values(&self) -> Vec<String> {
+        let mut values: Vec<String> = self.entered.borrow().values().cloned().collect();
+        for name in self.looked_up.borrow().iter() {
+            values.extend(self.resolve_if_unlocked(name).map(|(value, _)| value));
+        }
+        values.extend(self.providers.resolved_values());
+        values
+    }
+
+    /// Where a provider placeholder would be resolved from, if anywhere.
+    pub fn provider_source(&self, provider: &Provider) -> Option<&'static str> {
+        self.providers.source(provider)
+    }
+}
+
+impl VariableSource for Variables {
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.looked_up.borrow_mut().insert(name.to_string());
+        self.resolve(name).map(|(value, _)| value)
+    }
+
+    fn provide(&self, provider: &Provider) -> Result<String, String> {
+        self.providers.resolve(provider)
+    }
+}
+
+/// Variables from the environment and env files only, for commands that must not prompt.
+///
+/// The secret store stays locked and provider placeholders are kept as written.
+pub struct WithoutSecrets<'a>(pub &'a Variables);
+
+impl VariableSource for WithoutSecrets<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.0.looked_up.borrow_mut().insert(name.to_string());
+        self.0.resolve_unlocked(name).map(|(value, _)| value)
+    }
+
+    fn runs_providers(&self) -> bool {
+        false
+    }
+}
+
+/// Parse a dotenv-style file: `KEY=value` lines with optional `export`, quotes and comments.
+pub fn parse_env_file(path: &Path) -> Result<BTreeMap<String, String>, AppError> {
+    let contents = fs::read_to_string(path)?;
+    let mut values = BTreeMap::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let invalid =
+            |reason: &str| AppError::config(format!("{}:{}: {reason}", path.display(), index + 1));
+        let (key, raw) = line.split_once('=').ok_or_else(|| invalid("expected KEY=value"))?;
+        let key = key.trim();
+        if !super::placeholder::is_var_name(key) {
+            return Err(invalid(&format!("\"{key}\" is not a valid variable name")));
+        }
+        values.insert(
+            key.to_string(),
+            parse_value(raw.trim()).ok_or_else(|| invalid("unterminated quote"))?,
+        );
+    }
+
+    Ok(values)
+}
+
+/// Set `key` in a dotenv file, replacing an existing assignment or appending a new one.
+///
+/// The file holds secrets, so it is written with owner-only permissions.
+pub fn set_env_value(path: &Path, key: &str, value: &str) -> Result<(), AppError> {
+    let existing = if path.exists() { fs::read_to_string(path)? } else { String::new() };
+    let assignment = format!("{key}={}", quote_value(value));
+    let mut replaced = false;
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+            let assigns_key = trimmed.split_once('=').is_some_and(|(name, _)| name.trim() == key);
+            if assigns_key && !replaced {
+                replaced = true;
+                assignment.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(assignment);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    super::sensitive::write_private(path, &format!("{}\n", lines.join("\n")))
+}
+
+fn quote_value(value: &str) -> String {
+    let plain = value.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:@+,=".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+fn parse_value(raw: &str) -> Option<String> {
+    if let Some(body) = raw.strip_prefix('"') {
+        let end = body.rfind('"')?;
+        let mut value = String::new();
+        let mut chars = body[..end].chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some('n')) => {
+                    value.push('\n');
+                    chars.next();
+                }
+                ('\\', Some(escaped @ ('"' | '\\'))) => {
+                    value.push(escaped);
+                    chars.next();
+                }
+                _ => value.push(c),
+            }
+        }
+        Some(value)
+    } else if let Some(body) = raw.strip_prefix('\'') {
+        body.rfind('\'').map(|end| body[..end].to_string())
+    } else {
+        let value = raw.split(" #").next().unwrap_or_default();
+        Some(value.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dotenv_syntax() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".mms.env");
+        fs::write(
+            &path,
+            "# comment\nexport PLAIN=value # trailing\nQUOTED=\"a \\\"b\\\"\\nc\"\nSINGLE='$raw'\n\n",
+        )
+        .unwrap();
+
+        let values = parse_env_file(&path).unwrap();
+        assert_eq!(values["PLAIN"], "value");
+        assert_eq!(values["QUOTED"], "a \"b\"\nc");
+        assert_eq!(values["SINGLE"], "$raw");
+    }
+
+    #[test]
+    fn set_env_value_round_trips_through_the_parser() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".mms.env");
+        fs::write(&path, "# keep me\nexport TOKEN=old\nOTHER=1\n").unwrap();
+
+        set_env_value(&path, "TOKEN", "new \"quoted\" #value\\").unwrap();
+        set_env_value(&path, "ADDED", "plain-value").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# keep me\n"));
+        let values = parse_env_file(&path).unwrap();
+        assert_eq!(values["TOKEN"], "new \"quoted\" #value\\");
+        assert_eq!(values["OTHER"], "1");
+        assert_eq!(values["ADDED"], "plain-value");
+    }
+
+    #[test]
+    fn rejects_malformed_lines_with_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("env");
+        fs::write(&path, "OK=1\nnot a pair\n").unwrap();
+
+        let err = parse_env_file(&path).unwrap_err().to_string();
+        assert!(err.contains(":2: expected KEY=value"), "{err}");
+    }
+}
@@ -1,5 +1,7 @@
 use crate::config::model::McpCatalogue;
+use crate::config::sensitive;
 use crate::error::AppError;
+use crate::integration::native;
 use std::fs;
 use std::path::PathBuf;
 use toml_edit::{value, Array, DocumentMut, Item, Table};
@@ -17,7 +19,47 @@ impl CodexSync {
             return Ok(None);
         }
 
+        let serialised = Self::render(&fs::read_to_string(&codex_config)?, catalogue)?;
+        if catalogue.holds_secrets() {
+            sensitive::write_private(&codex_config, &serialised)?;
+        } else {
+            fs::write(&codex_config, serialised)?;
+        }
+        Ok(Some(codex_config))
+    }
+
+    /// Whether the Codex config already holds exactly `catalogue`; `None` if it is absent.
+    pub fn is_current(
+        paths: &crate::config::paths::MmsPaths,
+        catalogue: &McpCatalogue,
+    ) -> Result<Option<bool>, AppError> {
+        let codex_config = paths.codex_config_path();
+        if !codex_config.exists() {
+            return Ok(None);
+        }
         let contents = fs::read_to_string(&codex_config)?;
+        Ok(Some(Self::render(&contents, catalogue)? == contents))
+    }
+
+    /// Names of the servers in the Codex `mcp_servers` table; `None` if the config is absent.
+    pub fn configured(
+        paths: &crate::config::paths::MmsPaths,
+    ) -> Result<Option<Vec<String>>, AppError> {
+        let codex_config = paths.codex_config_path();
+        if !codex_config.exists() {
+            return Ok(None);
+        }
+        let doc: DocumentMut = fs::read_to_string(&codex_config)?.parse()?;
+        let servers = doc.get("mcp_servers").and_then(Item::as_table_like);
+        Ok(Some(
+            servers
+                .map(|table| table.iter().map(|(name, _)| name.to_string()).collect())
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// `contents` with its `mcp_servers` table replaced by `catalogue`.
+    fn render(contents: &str, catalogue: &McpCatalogue) -> Result<String, AppError> {
         let mut doc: DocumentMut = contents.parse()?;
 
         doc.remove("mcp_servers");
@@ -46,15 +88,43 @@ impl CodexSync {
                 server_table["args"] = Item::Value(array.into());
             }
 
-            if !server.env.is_empty() {
+            let passthrough = native::codex_passthrough(server);
+            let env: Vec<_> =
+                server.env.iter().filter(|(key, _)| !passthrough.contains(key)).collect();
+            if !env.is_empty() {
                 let mut env_table = Table::new();
                 env_table.set_implicit(false);
-                for (key, val) in &server.env {
+                for (key, val) in env {
                     env_table[key] = value(val.clone());
                 }
                 server_table["env"] = Item::Table(env_table);
             }
 
+            if !passthrough.is_empty() {
+                let mut array = Array::new();
+                for name in &passthrough {
+                    array.push(name.as_str());
+                }
+                server_table["env_vars"] = Item::Value(array.into());
+            }
+
+            if let Some(cwd) = &server.cwd {
+                server_table["cwd"] = value(cwd.clone());
+            }
+
+            if let Some(url) = &server.url {
+                server_table["url"] = value(url.clone());
+            }
+
+            if !server.headers.is_empty() {
+                let mut headers_table = Table::new();
+                headers_table.set_implicit(false);
+                for (key, val) in &server.headers {
+                    headers_table[key] = value(val.clone());
+                }
+                server_table["http_headers"] = Item::Table(headers_table);
+            }
+
             if let Some(timeout) = server.timeout {
                 server_table["timeout"] = value(timeout as i64);
             }
@@ -62,8 +132,6 @@ impl CodexSync {
             doc["mcp_servers"][name] = Item::Table(server_table);
         }
 
-        let serialised = doc.to_string();
-        fs::write(&codex_config, serialised)?;
-        Ok(Some(codex_config))
+        Ok(doc.to_string())
     }
 }
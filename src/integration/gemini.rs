@@ -1,4 +1,5 @@
 use crate::config::model::McpCatalogue;
+use crate::config::sensitive;
 use crate::error::AppError;
 use serde_json::{json, Value};
 use std::fs;
@@ -29,8 +30,37 @@ impl GeminiSync {
             )));
         }
 
-        let serialised = serde_json::to_string_pretty(&settings)?;
-        fs::write(&settings_path, format!("{serialised}\n"))?;
+        let serialised = format!("{}\n", serde_json::to_string_pretty(&settings)?);
+        if catalogue.holds_secrets() {
+            sensitive::write_private(&settings_path, &serialised)?;
+        } else {
+            fs::write(&settings_path, serialised)?;
+        }
         Ok(settings_path)
     }
+
+    /// Whether `settings.json` already lists exactly `catalogue`'s servers; `None` if it is absent.
+    pub fn is_current(
+        workspace: &Path,
+        catalogue: &McpCatalogue,
+    ) -> Result<Option<bool>, AppError> {
+        let settings_path = workspace.join(".gemini").join("settings.json");
+        if !settings_path.exists() {
+            return Ok(None);
+        }
+        let settings: Value = serde_json::from_str(&fs::read_to_string(&settings_path)?)?;
+        let expected = serde_json::to_value(&catalogue.mcp_servers)?;
+        Ok(Some(settings.get("mcpServers") == Some(&expected)))
+    }
+
+    /// Names of the servers `settings.json` lists; `None` if it is absent.
+    pub fn configured(workspace: &Path) -> Result<Option<Vec<String>>, AppError> {
+        let settings_path = workspace.join(".gemini").join("settings.json");
+        if !settings_path.exists() {
+            return Ok(None);
+        }
+        let settings: Value = serde_json::from_str(&fs::read_to_string(&settings_path)?)?;
+        let servers = settings.get("mcpServers").and_then(Value::as_object);
+        Ok(Some(servers.map(|servers| servers.keys().cloned().collect()).unwrap_or_default()))
+    }
 }
@@ -2,6 +2,7 @@
 
 pub mod codex;
 pub mod gemini;
+pub mod native;
 
 pub use codex::CodexSync;
 pub use gemini::GeminiSync;
@@ -0,0 +1,250 @@
+use crate::config::model::{McpCatalogue, McpServer};
+use crate::config::placeholder::{self, Fallback, Segment, VariableSource};
+use crate::error::AppError;
+
+/// A sync target with its own way of referring to environment variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Gemini CLI expands `$VAR` and `${VAR}` anywhere in `settings.json`.
+    Gemini,
+    /// Codex only forwards whole variables from its own environment via `env_vars`.
+    Codex,
+}
+
+impl Target {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Gemini => "Gemini",
+            Self::Codex => "Codex",
+        }
+    }
+}
+
+/// Rewrite placeholders in `catalogue` into `target`'s native syntax.
+///
+/// Placeholders the target cannot express are resolved instead, with a warning per field, and
+/// their servers are marked as holding secrets.
+pub fn translate(
+    catalogue: &McpCatalogue,
+    target: Target,
+    vars: &dyn VariableSource,
+) -> Result<(McpCatalogue, Vec<String>), AppError> {
+    let mut translated = catalogue.clone();
+    let mut warnings = Vec::new();
+    let mut failures = Vec::new();
+    let mut resolved = Vec::new();
+
+    for (name, server) in translated.mcp_servers.iter_mut() {
+        let mut substituted = false;
+        let result = placeholder::for_each_field(server, |field, value| {
+            // Left for `CodexSync` to write as `env_vars`.
+            let passthrough =
+                field.strip_prefix("env.").is_some_and(|key| is_whole_variable(value, key));
+            if target == Target::Codex && passthrough {
+                return Ok(());
+            }
+            let segments = placeholder::parse(value)?;
+            // A bare `$NAME` is left for Gemini on purpose; only escaped `$$` is meant literally.
+            if target == Target::Gemini && value.contains("$$") && rereads_literal_dollar(&segments)
+            {
+                warnings.push(format!(
+                    "Gemini expands $NAME in its settings and has no escape for a literal $, so \
+                     MCP server '{name}' {field} may reach the server changed"
+                ));
+            }
+            if let Some(native) = native_form(&segments, target) {
+                *value = native;
+                return Ok(());
+            }
+            let expansion = placeholder::expand(value, vars)
+                .map_err(|err| format!("MCP server '{name}' {field}: {err}"))?;
+            warnings.push(format!(
+                "{} has no native form for placeholders in MCP server '{name}' {field}; \
+                 wrote the resolved value",
+                target.label()
+            ));
+            substituted |= expansion.substituted;
+            *value = expansion.value;
+            Ok(())
+        });
+        match result {
+            Ok(()) if substituted => resolved.push(name.clone()),
+            Ok(()) => {}
+            Err(err) => failures.push(format!("  - {err}")),
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(AppError::config(format!(
+            "could not resolve placeholders:\n{}",
+            failures.join("\n")
+        )));
+    }
+    for name in resolved {
+        translated.mark_resolved(&name, true);
+    }
+    Ok((translated, warnings))
+}
+
+/// Env keys Codex can forward from its own environment: entries written as `"NAME": "${NAME}"`.
+pub fn codex_passthrough(server: &McpServer) -> Vec<String> {
+    server
+        .env
+        .iter()
+        .filter(|(key, value)| is_whole_variable(value, key))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+/// Whether `value` is exactly `${name}` (or `${name:?...}`).
+fn is_whole_variable(value: &str, name: &str) -> bool {
+    matches!(
+        placeholder::parse(value).as_deref(),
+        Ok([Segment::Variable { name: var, fallback: Fallback::Keep | Fallback::Required(_), .. }])
+            if var == name
+    )
+}
+
+/// The field rewritten for `target`, or `None` when some placeholder has no native equivalent.
+fn native_form(segments: &[Segment], target: Target) -> Option<String> {
+    let mut native = String::new();
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(text) => native.push_str(text),
+            Segment::Variable {
+                name, fallback: Fallback::Keep | Fallback::Required(_), ..
+            } if target == Target::Gemini => {
+                let followed_by_word = matches!(
+                    segments.get(index + 1),
+                    Some(Segment::Literal(next))
+                        if next.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                );
+                if followed_by_word {
+                    native.push_str(&format!("${{{name}}}"));
+                } else {
+                    native.push('$');
+                    native.push_str(name);
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(native)
+}
+
+/// Whether Gemini would read a literal `$` as the start of a `$VAR` or `${VAR}` reference.
+fn rereads_literal_dollar(segments: &[Segment]) -> bool {
+    segments.iter().enumerate().any(|(index, segment)| {
+        let Segment::Literal(text) = segment else { return false };
+        text.match_indices('$').any(|(at, _)| {
+            let next = text[at + 1..].chars().next().or_else(|| match segments.get(index + 1) {
+                Some(Segment::Literal(next)) => next.chars().next(),
+                _ => None,
+            });
+            next.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '{')
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::McpServer;
+
+    struct Vars;
+
+    impl VariableSource for Vars {
+        fn lookup(&self, name: &str) -> Option<String> {
+            (name == "REGION").then(|| "eu".to_string())
+        }
+    }
+
+    fn catalogue() -> McpCatalogue {
+        let server = McpServer {
+            command: Some("srv".into()),
+            args: vec!["--token=${API_TOKEN}".into(), "--region=${REGION:-us}".into()],
+            env: [
+                ("API_TOKEN".to_string(), "${API_TOKEN}".to_string()),
+                ("RENAMED".to_string(), "${API_TOKEN}".to_string()),
+            ]
+            .into(),
+            headers: [("Authorization".to_string(), "Bearer ${API_TOKEN}x".to_string())].into(),
+            ..McpServer::default()
+        };
+        let mut catalogue = McpCatalogue::empty();
+        catalogue.mcp_servers.insert("demo".into(), server);
+        catalogue
+    }
+
+    #[test]
+    fn gemini_uses_dollar_references() {
+        let (translated, warnings) = translate(&catalogue(), Target::Gemini, &Vars).unwrap();
+        let server = &translated.mcp_servers["demo"];
+        assert_eq!(server.args, vec!["--token=$API_TOKEN", "--region=eu"]);
+        assert_eq!(server.env["RENAMED"], "$API_TOKEN");
+        assert_eq!(server.headers["Authorization"], "Bearer ${API_TOKEN}x");
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(translated.holds_secrets());
+    }
+
+    #[test]
+    fn codex_passes_matching_env_through_and_falls_back_elsewhere() {
+        let (translated, warnings) = translate(&catalogue(), Target::Codex, &Vars).unwrap();
+        let server = &translated.mcp_servers["demo"];
+        assert_eq!(codex_passthrough(server), ["API_TOKEN"]);
+        assert_eq!(server.env["API_TOKEN"], "${API_TOKEN}");
+        // Unset variables stay as written; there is nothing better to emit.
+        assert_eq!(server.env["RENAMED"], "${API_TOKEN}");
+        assert_eq!(server.args[1], "--region=eu");
+        assert_eq!(warnings.len(), 4, "{warnings:?}");
+    }
+
+    /// Gemini's own expansion: set `$VAR` and `${VAR}` references are replaced, others kept.
+    fn gemini_expand(value: &str, env: &[(&str, &str)]) -> String {
+        let mut out = String::new();
+        let mut rest = value;
+        while let Some(at) = rest.find('$') {
+            out.push_str(&rest[..at]);
+            let after = &rest[at + 1..];
+            let (name, len) = match after.strip_prefix('{').and_then(|body| body.split_once('}')) {
+                Some((name, _)) if !name.is_empty() => (name, name.len() + 2),
+                _ => {
+                    let len = after
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(after.len());
+                    (&after[..len], len)
+                }
+            };
+            match env.iter().find(|(key, _)| !name.is_empty() && *key == name) {
+                Some((_, value)) => out.push_str(value),
+                None => out.push_str(&rest[at..at + 1 + len]),
+            }
+            rest = &after[len..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    #[test]
+    fn gemini_keeps_escaped_dollars_literal() {
+        let env = [("HOME", "/home/me"), ("API_TOKEN", "t0k")];
+        let server = McpServer {
+            command: Some("srv".into()),
+            args: vec!["pa$$".into(), "$$ 5".into(), "$$${API_TOKEN}".into(), "$$HOME".into()],
+            ..McpServer::default()
+        };
+        let mut catalogue = McpCatalogue::empty();
+        catalogue.mcp_servers.insert("demo".into(), server);
+
+        let (translated, warnings) = translate(&catalogue, Target::Gemini, &Vars).unwrap();
+        let received: Vec<String> = translated.mcp_servers["demo"]
+            .args
+            .iter()
+            .map(|arg| gemini_expand(arg, &env))
+            .collect();
+        assert_eq!(received[..3], ["pa$", "$ 5", "$t0k"]);
+        // Gemini has no escape for `$HOME`, so that field is flagged instead.
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("'demo' args"), "{warnings:?}");
+    }
+}
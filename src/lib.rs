@@ -5,35 +5,157 @@ pub mod config;
 pub mod error;
 pub mod integration;
 
-use cli::{CleanSelection, Commands};
-use config::{GlobalCatalogue, LocalCatalogue, MmsPaths};
+use cli::{CatalogueAction, CleanSelection, Commands, GlobalAction, MasterAction, SecretAction};
+use config::lock::{LockOutcome, Lockfile, SystemResolver};
+use config::master::{self, Recovery, ServerChange};
+use config::merge;
+use config::migrate::{self as migration, MigrationStatus};
+use config::missing;
+use config::placeholder::{self, Fallback, Segment, VariablesOnly};
+use config::redact::{self, Redactor};
+use config::registry;
+use config::schema::SchemaKind;
+use config::search::{self as finder, Field};
+use config::secret_store::{self, SecretStore};
+use config::signature::{self, SIGNATURE_SUFFIX};
+use config::sources;
+use config::trust::{self, Definition, TrustStore};
+use config::validate as validation;
+use config::variables::WithoutSecrets;
+use config::{
+    CatalogueStack, GlobalCatalogue, LocalCatalogue, MasterCatalogue, McpCatalogue, McpServer,
+    MmsPaths, MmsSettings, PlaceholderMode, Variables,
+};
 use error::AppError;
+use integration::native::{self, Target};
 use integration::{CodexSync, GeminiSync};
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 pub struct CommandContext {
     pub paths: MmsPaths,
     pub start_dir: PathBuf,
     pub verbose: bool,
+    pub show_secrets: bool,
+    pub recovery: Recovery,
+    pub env_files: Vec<PathBuf>,
+    settings: OnceCell<MmsSettings>,
+    vars: OnceCell<Variables>,
+    log_redactor: OnceCell<Redactor>,
 }
 
 impl CommandContext {
+    pub fn new(
+        paths: MmsPaths,
+        start_dir: PathBuf,
+        verbose: bool,
+        show_secrets: bool,
+        recovery: Recovery,
+        env_files: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            paths,
+            start_dir,
+            verbose,
+            show_secrets,
+            recovery,
+            env_files,
+            settings: OnceCell::new(),
+            vars: OnceCell::new(),
+            log_redactor: OnceCell::new(),
+        }
+    }
+
+    /// Settings from `config.toml`, loaded on first use so commands that never need them
+    /// still work when the file is broken.
+    pub fn settings(&self) -> Result<&MmsSettings, AppError> {
+        if let Some(settings) = self.settings.get() {
+            return Ok(settings);
+        }
+        let settings = MmsSettings::load(&self.paths)?;
+        Ok(self.settings.get_or_init(|| settings))
+    }
+
+    /// Placeholder variables for the workspace, loaded on first use.
+    pub fn vars(&self) -> Result<&Variables, AppError> {
+        if let Some(vars) = self.vars.get() {
+            return Ok(vars);
+        }
+        let workspace =
+            LocalCatalogue::discover(&self.start_dir, &self.paths.global_catalogue_path())
+                .and_then(|path| path.parent().map(Path::to_path_buf))
+                .unwrap_or_else(|| self.start_dir.clone());
+        let vars = Variables::load(&self.paths, self.settings()?, &workspace, &self.env_files)?;
+        Ok(self.vars.get_or_init(|| vars))
+    }
+
     fn log(&self, message: &str) {
         if self.verbose {
-            println!("{message}");
+            let redactor = self.log_redactor.get_or_init(|| self.redactor(None));
+            println!("{}", redactor.text(message));
+        }
+    }
+
+    /// Redactor for output, also hiding values substituted into `catalogue` entries.
+    fn redactor(&self, catalogue: Option<&McpCatalogue>) -> Redactor {
+        if self.show_secrets {
+            return Redactor::disabled();
         }
+        let mut known = self.vars.get().map(Variables::known_values).unwrap_or_default();
+        if let Some(catalogue) = catalogue.filter(|c| !c.meta.resolved_servers.is_empty()) {
+            if let Some(master) = MasterCatalogue::read_installed(&self.paths) {
+                for name in &catalogue.meta.resolved_servers {
+                    let template = master.mcp_servers.get(name);
+                    if let (Some(template), Some(server)) =
+                        (template, catalogue.mcp_servers.get(name))
+                    {
+                        known.extend(redact::substituted_values(template, server));
+                    }
+                }
+            }
+        }
+        Redactor::new(known)
     }
 }
 
 pub fn execute(command: Commands, context: CommandContext) -> Result<(), AppError> {
+    let result = dispatch(command, &context);
+    for line in context.vars.get().map(Variables::lookup_report).unwrap_or_default() {
+        context.log(&format!("Variable {line}"));
+    }
+    // Failing providers report their command and its stderr, which may carry secrets.
+    result.map_err(|err| match err {
+        AppError::Config(message) => AppError::Config(context.redactor(None).text(&message)),
+        err => err,
+    })
+}
+
+fn dispatch(command: Commands, context: &CommandContext) -> Result<(), AppError> {
     match command {
-        Commands::Init { from_global } => init(from_global, &context),
-        Commands::List => list(&context),
-        Commands::Add { names } => add(names, &context),
-        Commands::Remove { name } => remove(name, &context),
-        Commands::Command { name, copy } => show_command(name, copy, &context),
-        Commands::Sync { skip_codex, skip_gemini } => sync(skip_codex, skip_gemini, &context),
-        Commands::Clean { selection } => clean(selection.normalised(), &context),
+        Commands::Init { from_global } => init(from_global, context),
+        Commands::List => list(context),
+        Commands::Add { names, link } => add(names, link, context),
+        Commands::Remove { name } => remove(name, context),
+        Commands::Search { query } => search(query.join(" "), context),
+        Commands::Show { name } => show(name, context),
+        Commands::Command { name, copy } => show_command(name, copy, context),
+        Commands::Sync { skip_codex, skip_gemini } => sync(skip_codex, skip_gemini, context),
+        Commands::Lock { update } => lock(update, context),
+        Commands::Validate { path } => validate(path, context),
+        Commands::Env { global, strict } => env_report(global, strict, context),
+        Commands::Status => status(context),
+        Commands::Schema { kind } => schema(kind),
+        Commands::Migrate { check, paths } => migrate(check, paths, context),
+        Commands::Global { action: GlobalAction::Upgrade { interactive, dry_run } } => {
+            upgrade_global(interactive, dry_run, context)
+        }
+        Commands::Master { action } => master(action, context),
+        Commands::Import { registry, into, dry_run } => import(registry, into, dry_run, context),
+        Commands::Trust { names } => trust(names, context),
+        Commands::Catalogue { action } => catalogue(action, context),
+        Commands::Secret { key_file, action } => secret(action, key_file, context),
+        Commands::Clean { selection } => clean(selection.normalised(), context),
     }
 }
 
@@ -42,7 +164,12 @@ fn init(from_global: bool, ctx: &CommandContext) -> Result<(), AppError> {
     ctx.log(&format!("Initialising local catalogue in {}", cwd.display()));
 
     if from_global {
-        let global = GlobalCatalogue::ensure(&ctx.paths)?;
+        let global = GlobalCatalogue::ensure(
+            &ctx.paths,
+            ctx.settings()?.placeholders,
+            ctx.vars()?,
+            ctx.recovery,
+        )?;
         let path = LocalCatalogue::init_from_global(cwd, &global)?;
         println!("✅ Created {path}", path = path.display());
     } else {
@@ -53,17 +180,22 @@ fn init(from_global: bool, ctx: &CommandContext) -> Result<(), AppError> {
 }
 
 fn list(ctx: &CommandContext) -> Result<(), AppError> {
-    let catalogue = GlobalCatalogue::ensure(&ctx.paths)?;
+    let stack = catalogue_stack(ctx, true)?;
+    let catalogue = &stack.catalogue;
     if catalogue.mcp_servers.is_empty() {
         println!("No MCP servers found in {}", ctx.paths.global_catalogue_path().display());
         return Ok(());
     }
 
+    let redactor = ctx.redactor(Some(catalogue));
     println!("Available MCP servers:\n=====================");
     for (name, server) in catalogue.mcp_servers.iter() {
-        println!("[{name}]");
+        match stack.origin(name) {
+            Some(layer) => println!("[{name}] ({layer})"),
+            None => println!("[{name}]"),
+        }
         if let Some(command) = server.render_command() {
-            println!("{command}");
+            println!("{}", redactor.text(&command));
         } else {
             println!("(no command defined)");
         }
@@ -75,18 +207,47 @@ fn list(ctx: &CommandContext) -> Result<(), AppError> {
     Ok(())
 }
 
-fn add(names: Vec<String>, ctx: &CommandContext) -> Result<(), AppError> {
+fn search(query: String, ctx: &CommandContext) -> Result<(), AppError> {
+    let stack = catalogue_stack(ctx, true)?;
+    let hits = finder::search(&stack.catalogue, &query);
+    if hits.is_empty() {
+        println!("No MCP servers match '{query}'");
+        return Ok(());
+    }
+    let local = match LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path()) {
+        Some(path) => Some(migration::read_catalogue(&path)?),
+        None => None,
+    };
+
+    for hit in hits {
+        let mut heading = format!("[{}]", hit.name);
+        if let Some(layer) = stack.origin(&hit.name) {
+            heading.push_str(&format!(" ({layer})"));
+        }
+        if local.as_ref().is_some_and(|local| local.mcp_servers.contains_key(&hit.name)) {
+            heading.push_str(" ✅ in .mcp.json");
+        }
+        println!("{heading}");
+        if let Some(description) = &stack.catalogue.mcp_servers[&hit.name].description {
+            println!("- {description}");
+        }
+        if matches!(hit.field, Field::Package | Field::Tag) {
+            println!("  matched {} '{}'", hit.field.label(), hit.matched);
+        }
+    }
+    Ok(())
+}
+
+fn add(names: Vec<String>, link: bool, ctx: &CommandContext) -> Result<(), AppError> {
     let (mut local, local_path) =
         LocalCatalogue::load(&ctx.start_dir, &ctx.paths.global_catalogue_path())?;
-    let global = GlobalCatalogue::ensure(&ctx.paths)?;
-    let mut modified = false;
+    let stack = catalogue_stack(ctx, false)?;
+    let global = &stack.catalogue;
+    let mut added = Vec::new();
 
     for name in names {
         if !global.mcp_servers.contains_key(&name) {
-            println!(
-                "⚠️  MCP server '{name}' not found in {}",
-                ctx.paths.global_catalogue_path().display()
-            );
+            println!("⚠️  MCP server '{name}' not found in the master, team or user catalogues");
             continue;
         }
 
@@ -95,24 +256,56 @@ fn add(names: Vec<String>, ctx: &CommandContext) -> Result<(), AppError> {
             continue;
         }
 
-        if let Some(server) = global.mcp_servers.get(&name) {
-            local.mcp_servers.insert(name.clone(), server.clone());
-            println!("✅ Added '{name}' to {}", local_path.display());
-            modified = true;
+        if !added.contains(&name) {
+            added.push(name);
         }
     }
+    if added.is_empty() {
+        return Ok(());
+    }
 
-    if modified {
-        LocalCatalogue::save(&local_path, &local)?;
+    let workspace = local_path.parent().unwrap_or(&ctx.start_dir);
+    let mut missing = missing::find_missing(
+        global.mcp_servers.iter().filter(|(name, _)| added.contains(name)),
+        ctx.vars()?,
+    );
+    // Plain `${VAR}` placeholders are left for the target to resolve, as in `sync`.
+    missing.retain(|variable| variable.required);
+    missing::supply_missing(&missing, &ctx.paths, ctx.settings()?, ctx.vars()?, workspace)?;
+
+    for name in added {
+        if link {
+            local.mcp_servers.insert(name.clone(), McpServer::reference(&name));
+            println!("✅ Linked '{name}' in {}", local_path.display());
+        } else if let Some(source) = global.mcp_servers.get(&name) {
+            let mut server = source.clone();
+            let mut resolved = global.meta.resolved_servers.contains(&name);
+            // Values just supplied are written in; providers wait for `sync`.
+            if !missing.is_empty() && ctx.settings()?.placeholders == PlaceholderMode::Eager {
+                let vars = VariablesOnly(ctx.vars()?);
+                resolved |= placeholder::expand_server(&name, &mut server, &vars)?;
+            }
+            local.mcp_servers.insert(name.clone(), server);
+            local.mark_resolved(&name, resolved);
+            local.mark_copied(&name, Some(source));
+            match stack.origin(&name) {
+                Some(layer) => {
+                    println!("✅ Added '{name}' from {layer} to {}", local_path.display())
+                }
+                None => println!("✅ Added '{name}' to {}", local_path.display()),
+            }
+        }
     }
 
-    Ok(())
+    LocalCatalogue::save(&local_path, &local)
 }
 
 fn remove(name: String, ctx: &CommandContext) -> Result<(), AppError> {
     let (mut local, local_path) =
         LocalCatalogue::load(&ctx.start_dir, &ctx.paths.global_catalogue_path())?;
     if local.mcp_servers.remove(&name).is_some() {
+        local.mark_resolved(&name, false);
+        local.mark_copied(&name, None);
         LocalCatalogue::save(&local_path, &local)?;
         println!("🗑️  Removed '{name}' from {}", local_path.display());
     } else {
@@ -121,18 +314,154 @@ fn remove(name: String, ctx: &CommandContext) -> Result<(), AppError> {
     Ok(())
 }
 
-fn show_command(name: String, copy: bool, ctx: &CommandContext) -> Result<(), AppError> {
-    let catalogue = GlobalCatalogue::ensure(&ctx.paths)?;
+fn show(name: String, ctx: &CommandContext) -> Result<(), AppError> {
+    let stack = catalogue_stack(ctx, true)?;
+    let catalogue = &stack.catalogue;
     let Some(server) = catalogue.mcp_servers.get(&name) else {
+        return Err(AppError::config(format!(
+            "MCP server '{name}' not found in the project, user, team or master catalogues"
+        )));
+    };
+    let redactor = ctx.redactor(Some(catalogue));
+    let master = match MasterCatalogue::read_installed(&ctx.paths) {
+        Some(master) => master,
+        None => MasterCatalogue::embedded()?,
+    };
+
+    match stack.origin(&name) {
+        Some(layer) => println!("[{name}] ({layer})"),
+        None => println!("[{name}]"),
+    }
+    println!("{}", redactor.text(&serde_json::to_string_pretty(server)?));
+
+    // Inspecting an entry must not run its providers or unlock the secret store.
+    let vars = WithoutSecrets(ctx.vars()?);
+    let mut expanded = server.clone();
+    let rendered = placeholder::expand_server(&name, &mut expanded, &vars)
+        .ok()
+        .and_then(|_| expanded.render_command().or_else(|| expanded.url.clone()));
+    match rendered {
+        Some(command) => println!("Command:    {}", redactor.text(&command)),
+        None => println!("Command:    ⚠️  cannot be rendered until the variables below are set"),
+    }
+
+    // Entries resolved into ~/.mcp.json no longer show their placeholders; use the template.
+    let template = match catalogue.meta.resolved_servers.contains(&name) {
+        true => master.mcp_servers.get(&name).unwrap_or(server),
+        false => server,
+    };
+    let mut rows: Vec<[String; 4]> = Vec::new();
+    for (field, segment) in placeholder::server_placeholders(template) {
+        let Some((label, kind, status, _)) =
+            describe_segment(&segment, ctx.vars()?, false, ctx.show_secrets)
+        else {
+            continue;
+        };
+        match rows.iter_mut().find(|row| row[0] == label) {
+            Some(row) if !row[3].split(", ").any(|known| known == field) => {
+                row[3].push_str(&format!(", {field}"))
+            }
+            Some(_) => {}
+            None => rows.push([label, kind, status, field]),
+        }
+    }
+    if rows.is_empty() {
+        println!("Variables:  none");
+    } else {
+        println!("Variables:");
+        print_rows(&rows);
+    }
+
+    // Compare every copy by the values it stands for, so resolved and templated copies match.
+    let normalised = |server: &McpServer| {
+        let mut expanded = server.clone();
+        match placeholder::expand_server(&name, &mut expanded, &vars) {
+            Ok(_) => expanded,
+            Err(_) => server.clone(),
+        }
+    };
+    let in_effect = normalised(server);
+    let compare =
+        |copy: &McpServer| match master::changed_fields(&in_effect, &normalised(copy)).as_slice() {
+            [] => "same as the definition in effect".to_string(),
+            fields => format!("differs in {}", fields.join(", ")),
+        };
+    let mut scopes: Vec<[String; 3]> = Vec::new();
+    if let Some(copy) = master.mcp_servers.get(&name) {
+        let path = ctx.paths.master_catalogue_path();
+        scopes.push(["master".to_string(), path.display().to_string(), compare(copy)]);
+    }
+    for team in &ctx.settings()?.teams {
+        if let Some(copy) =
+            team.load(&ctx.paths).ok().and_then(|layer| layer.mcp_servers.get(&name).cloned())
+        {
+            let path = team.catalogue_path().display().to_string();
+            scopes.push([format!("team:{}", team.name), path, compare(&copy)]);
+        }
+    }
+    let global_path = ctx.paths.global_catalogue_path();
+    if let Some(copy) = GlobalCatalogue::load(&ctx.paths)?.mcp_servers.get(&name) {
+        scopes.push(["user".to_string(), global_path.display().to_string(), compare(copy)]);
+    }
+    let local_path = LocalCatalogue::discover(&ctx.start_dir, &global_path);
+    let local = match &local_path {
+        Some(path) => Some(migration::read_catalogue(path)?),
+        None => None,
+    };
+    if let (Some(path), Some(copy)) =
+        (&local_path, local.as_ref().and_then(|local| local.mcp_servers.get(&name)))
+    {
+        let status = match &copy.extends {
+            Some(target) => format!("extends '{target}'"),
+            None => compare(copy),
+        };
+        scopes.push(["project".to_string(), path.display().to_string(), status]);
+    }
+    println!("Scopes:");
+    print_rows(&scopes);
+
+    let workspace = local_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| ctx.start_dir.clone());
+    let in_project = local.as_ref().is_some_and(|local| local.mcp_servers.contains_key(&name));
+    let target = |configured: Option<Vec<String>>, absent: &str| match configured {
+        Some(names) if names.contains(&name) => "✅ included".to_string(),
+        Some(_) if in_project => "⚠️  not included yet; run `mms sync`".to_string(),
+        Some(_) => "– not included".to_string(),
+        None => format!("ℹ️  {absent}"),
+    };
+    println!("Targets:");
+    print_rows(&[
+        [
+            "Gemini".to_string(),
+            workspace.join(".gemini").join("settings.json").display().to_string(),
+            target(GeminiSync::configured(&workspace)?, "not synced yet"),
+        ],
+        [
+            "Codex".to_string(),
+            ctx.paths.codex_config_path().display().to_string(),
+            target(CodexSync::configured(&ctx.paths)?, "not configured"),
+        ],
+    ]);
+    Ok(())
+}
+
+fn show_command(name: String, copy: bool, ctx: &CommandContext) -> Result<(), AppError> {
+    let stack = catalogue_stack(ctx, true)?;
+    let catalogue = &stack.catalogue;
+    let Some(mut server) = catalogue.mcp_servers.get(&name).cloned() else {
         println!(
-            "⚠️  MCP server '{name}' not found in {}",
-            ctx.paths.global_catalogue_path().display()
+            "⚠️  MCP server '{name}' not found in the project, user, team or master catalogues"
         );
         return Ok(());
     };
+    placeholder::expand_server(&name, &mut server, ctx.vars()?)?;
 
     if let Some(command) = server.render_command() {
-        println!("Command for '{name}': {command}");
+        let layer = stack.origin(&name).map(|layer| format!(" ({layer})")).unwrap_or_default();
+        println!("Command for '{name}'{layer}: {}", ctx.redactor(Some(catalogue)).text(&command));
         if copy {
             copy_to_clipboard(&command);
         }
@@ -144,22 +473,53 @@ fn show_command(name: String, copy: bool, ctx: &CommandContext) -> Result<(), Ap
 }
 
 fn sync(skip_codex: bool, skip_gemini: bool, ctx: &CommandContext) -> Result<(), AppError> {
-    let (local, local_path) =
-        LocalCatalogue::load(&ctx.start_dir, &ctx.paths.global_catalogue_path())?;
+    if let Some(path) = LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path())
+    {
+        check_catalogue(&path)?;
+    }
+    let (mut local, local_path) = load_local_resolved(ctx)?;
     let workspace =
         local_path.parent().map(Path::to_path_buf).unwrap_or_else(|| ctx.start_dir.clone());
+    // Pin first, so the trust review covers the packages that are actually launched.
+    apply_lock(&mut local, &workspace, ctx)?;
+    review_trust(&local, ctx)?;
+    let native = ctx.settings()?.placeholders == PlaceholderMode::Native;
+    if !native {
+        // Unset `${VAR}` placeholders pass through for the target to resolve; only `:?` needs a value.
+        let mut missing = missing::find_missing(&local.mcp_servers, ctx.vars()?);
+        missing.retain(|variable| variable.required);
+        missing::supply_missing(&missing, &ctx.paths, ctx.settings()?, ctx.vars()?, &workspace)?;
+        ctx.log("Resolving placeholders for target configurations");
+        placeholder::expand_catalogue(&mut local, ctx.vars()?)?;
+    }
+    let for_target = |target: Target| -> Result<McpCatalogue, AppError> {
+        if !native {
+            return Ok(local.clone());
+        }
+        ctx.log("Translating placeholders into native variable references");
+        let (translated, warnings) = native::translate(&local, target, ctx.vars()?)?;
+        for warning in warnings {
+            eprintln!("⚠️  {warning}");
+        }
+        Ok(translated)
+    };
     ctx.log(&format!("Using workspace {}", workspace.display()));
 
     let mut updated = Vec::new();
 
     if !skip_gemini {
-        let settings_path = GeminiSync::sync(&workspace, &local)?;
+        let settings_path = GeminiSync::sync(&workspace, &for_target(Target::Gemini)?)?;
         println!("✅ Synced Gemini settings at {}", settings_path.display());
         updated.push("Gemini");
     }
 
     if !skip_codex {
-        match CodexSync::sync(&ctx.paths, &local)? {
+        let codex = if ctx.paths.codex_config_path().exists() {
+            CodexSync::sync(&ctx.paths, &for_target(Target::Codex)?)?
+        } else {
+            None
+        };
+        match codex {
             Some(path) => {
                 println!("✅ Synced Codex MCP block at {}", path.display());
                 updated.push("Codex");
@@ -177,6 +537,888 @@ fn sync(skip_codex: bool, skip_gemini: bool, ctx: &CommandContext) -> Result<(),
     Ok(())
 }
 
+/// Render the package versions pinned in the workspace's `.mcp.lock` into `catalogue`.
+fn apply_lock(
+    catalogue: &mut McpCatalogue,
+    workspace: &Path,
+    ctx: &CommandContext,
+) -> Result<(), AppError> {
+    if let Some(lock) = Lockfile::load(workspace)? {
+        ctx.log("Rendering package versions pinned in .mcp.lock");
+        for warning in lock.apply(catalogue) {
+            eprintln!("⚠️  {warning}");
+        }
+    }
+    Ok(())
+}
+
+/// Refuse to sync servers whose command, args or env keys changed since they were approved.
+fn review_trust(catalogue: &McpCatalogue, ctx: &CommandContext) -> Result<(), AppError> {
+    let mut store = TrustStore::load(&ctx.paths)?;
+    let (first_use, unapproved) = store.review(catalogue);
+    if !first_use.is_empty() {
+        store.save(&ctx.paths)?;
+        println!("ℹ️  Approved on first use: {}", first_use.join(", "));
+    }
+    if unapproved.is_empty() {
+        return Ok(());
+    }
+    let redactor = ctx.redactor(Some(catalogue));
+    for change in &unapproved {
+        eprintln!("⚠️  MCP server '{}' changed since it was approved:", change.name);
+        for line in trust::diff(&change.approved, &change.current) {
+            eprintln!("{}", redactor.text(&line));
+        }
+    }
+    let names: Vec<&str> = unapproved.iter().map(|change| change.name.as_str()).collect();
+    Err(AppError::config(format!(
+        "refusing to sync unapproved server definitions; review the changes above and run \
+         `mms trust {}`",
+        names.join(" ")
+    )))
+}
+
+/// Load the nearest local catalogue with any `extends` references expanded.
+fn load_local_resolved(ctx: &CommandContext) -> Result<(McpCatalogue, PathBuf), AppError> {
+    let (local, local_path) =
+        LocalCatalogue::load(&ctx.start_dir, &ctx.paths.global_catalogue_path())?;
+    if !local.has_references() {
+        return Ok((local, local_path));
+    }
+    ctx.log("Resolving linked entries against the catalogue stack");
+    let stack = catalogue_stack(ctx, false)?;
+    Ok((LocalCatalogue::resolve(&local, &stack.catalogue)?, local_path))
+}
+
+/// Resolve master, team and user catalogues, plus the nearest project catalogue if asked.
+fn catalogue_stack(ctx: &CommandContext, with_project: bool) -> Result<CatalogueStack, AppError> {
+    let stack = CatalogueStack::load(&ctx.paths, ctx.settings()?, ctx.vars()?, ctx.recovery)?;
+    if !with_project {
+        return Ok(stack);
+    }
+    match LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path()) {
+        Some(path) => stack.with_project(&migration::read_catalogue(&path)?),
+        None => Ok(stack),
+    }
+}
+
+fn lock(update: Vec<String>, ctx: &CommandContext) -> Result<(), AppError> {
+    let (local, local_path) = load_local_resolved(ctx)?;
+    if let Some(name) = update.iter().find(|name| !local.mcp_servers.contains_key(*name)) {
+        return Err(AppError::config(format!(
+            "MCP server '{name}' is not in {}",
+            local_path.display()
+        )));
+    }
+    let workspace =
+        local_path.parent().map(Path::to_path_buf).unwrap_or_else(|| ctx.start_dir.clone());
+    let mut lockfile = Lockfile::load(&workspace)?.unwrap_or_default();
+    let outcomes = lockfile.lock(&local, &update, &SystemResolver)?;
+
+    for outcome in &outcomes {
+        match outcome {
+            LockOutcome::Pinned { name, arg } => println!("🔒 Pinned '{name}' to {arg}"),
+            LockOutcome::Kept(name) => ctx.log(&format!("Kept the pin for '{name}'")),
+            LockOutcome::Exact(name) => {
+                println!("ℹ️  '{name}' already names an exact version in .mcp.json")
+            }
+            LockOutcome::Dropped(name) => println!("🗑️  Dropped '{name}' from .mcp.lock"),
+            LockOutcome::Failed { .. } => {}
+        }
+    }
+    for name in &update {
+        let pinned = outcomes.iter().any(|outcome| {
+            matches!(outcome, LockOutcome::Pinned { name: pinned, .. }
+                | LockOutcome::Failed { name: pinned, .. } if pinned == name)
+        });
+        let exact = outcomes.contains(&LockOutcome::Exact(name.clone()));
+        if !pinned && !exact {
+            println!("⚠️  '{name}' has no npm, PyPI or git package to pin");
+        }
+    }
+    lockfile.save(&workspace)?;
+    println!("✅ Wrote {}", Lockfile::path(&workspace).display());
+
+    let failures: Vec<String> = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            LockOutcome::Failed { name, error } => Some(format!("  - '{name}' ({error})")),
+            _ => None,
+        })
+        .collect();
+    if !failures.is_empty() {
+        return Err(AppError::config(format!(
+            "could not pin {} server(s); their previous pins, if any, were kept:\n{}",
+            failures.len(),
+            failures.join("\n")
+        )));
+    }
+    Ok(())
+}
+
+fn validate(path: Option<PathBuf>, ctx: &CommandContext) -> Result<(), AppError> {
+    let path = match path {
+        Some(path) => path,
+        None => LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path())
+            .ok_or_else(|| {
+                AppError::config("No .mcp.json found in current directory or any parent")
+            })?,
+    };
+    check_catalogue(&path)?;
+    println!("✅ {} is valid", path.display());
+    Ok(())
+}
+
+/// Print diagnostics for a catalogue file and fail when any of them is an error.
+fn check_catalogue(path: &Path) -> Result<(), AppError> {
+    let diagnostics = validation::validate_file(path)?;
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    if validation::has_errors(&diagnostics) {
+        return Err(AppError::config(format!("{} failed validation", path.display())));
+    }
+    Ok(())
+}
+
+fn env_report(global: bool, strict: bool, ctx: &CommandContext) -> Result<(), AppError> {
+    let (catalogue, source, targets) = if global {
+        let global_path = ctx.paths.global_catalogue_path();
+        let mut catalogue = GlobalCatalogue::ensure(
+            &ctx.paths,
+            ctx.settings()?.placeholders,
+            ctx.vars()?,
+            ctx.recovery,
+        )?;
+        // Resolved entries no longer carry placeholders; report the master templates instead.
+        if !catalogue.meta.resolved_servers.is_empty() {
+            let master = MasterCatalogue::load(&ctx.paths, ctx.recovery)?;
+            for name in &catalogue.meta.resolved_servers {
+                if let Some(template) = master.mcp_servers.get(name) {
+                    catalogue.mcp_servers.insert(name.clone(), template.clone());
+                }
+            }
+        }
+        let targets = match ctx.settings()?.placeholders {
+            PlaceholderMode::Eager => vec![global_path.display().to_string()],
+            PlaceholderMode::Late | PlaceholderMode::Native => {
+                vec!["project targets synced from it".to_string()]
+            }
+        };
+        (catalogue, global_path, targets)
+    } else {
+        let (catalogue, path) = load_local_resolved(ctx)?;
+        let workspace = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let targets = vec![
+            workspace.join(".gemini").join("settings.json").display().to_string(),
+            ctx.paths.codex_config_path().display().to_string(),
+        ];
+        (catalogue, path, targets)
+    };
+
+    println!("Placeholders in {}:", source.display());
+    let mut missing = Vec::new();
+    for (name, server) in &catalogue.mcp_servers {
+        let mut needs: Vec<(Segment, Vec<String>)> = Vec::new();
+        for (field, segment) in placeholder::server_placeholders(server) {
+            match needs.iter_mut().find(|(known, _)| *known == segment) {
+                Some((_, fields)) if !fields.contains(&field) => fields.push(field),
+                Some(_) => {}
+                None => needs.push((segment, vec![field])),
+            }
+        }
+        if needs.is_empty() {
+            continue;
+        }
+
+        println!("\n{name}");
+        let mut unresolved = Vec::new();
+        let mut rows = Vec::new();
+        for (segment, fields) in needs {
+            let Some((label, kind, status, is_missing)) =
+                describe_segment(&segment, ctx.vars()?, true, ctx.show_secrets)
+            else {
+                continue;
+            };
+            if let Segment::Variable { fallback: Fallback::Keep, raw, .. } = &segment {
+                if ctx.vars()?.resolve(&label).is_none() {
+                    unresolved.push(raw.clone());
+                }
+            }
+            if is_missing {
+                missing.push(label.clone());
+            }
+            rows.push([label, kind, status, fields.join(", ")]);
+        }
+        print_rows(&rows);
+        if !unresolved.is_empty() {
+            println!(
+                "  ⚠️  {} would be written unresolved to {}",
+                unresolved.join(", "),
+                targets.join(" and ")
+            );
+        }
+    }
+
+    if missing.is_empty() {
+        println!("\n✅ All required variables are available");
+        return Ok(());
+    }
+    missing.sort();
+    missing.dedup();
+    let summary = format!("Missing required variables: {}", missing.join(", "));
+    if strict {
+        return Err(AppError::config(summary));
+    }
+    println!("\n⚠️  {summary}");
+    Ok(())
+}
+
+fn status(ctx: &CommandContext) -> Result<(), AppError> {
+    let short = |hash: &str| hash.chars().take(12).collect::<String>();
+    let global_path = ctx.paths.global_catalogue_path();
+    let local_path = LocalCatalogue::discover(&ctx.start_dir, &global_path);
+    let workspace = local_path.as_deref().and_then(Path::parent).unwrap_or(&ctx.start_dir);
+    println!("Workspace:  {}", workspace.display());
+    match &local_path {
+        Some(path) => println!("Catalogue:  {}", path.display()),
+        None => println!("Catalogue:  none (no .mcp.json here or in any parent)"),
+    }
+
+    let embedded = format!(
+        "mms {} ({})",
+        MasterCatalogue::EMBEDDED_VERSION,
+        short(MasterCatalogue::EMBEDDED_SHA256)
+    );
+    let master_path = ctx.paths.master_catalogue_path();
+    let master = MasterCatalogue::read_installed(&ctx.paths);
+    let master_hash = match &master {
+        Some(master) => master.meta.master_hash.clone(),
+        None if !master_path.exists() => Some(MasterCatalogue::EMBEDDED_SHA256.to_string()),
+        None => None,
+    };
+    let master_status = if let Some(quarantined) = MasterCatalogue::quarantined(&ctx.paths)? {
+        format!(
+            "⚠️  quarantined at {}; run `mms master reset` or `mms master update`",
+            quarantined.display()
+        )
+    } else if !master_path.exists() {
+        format!("ℹ️  not installed yet; the catalogue embedded in {embedded} is used")
+    } else if master.is_none() {
+        "❌ cannot be parsed; the next command will quarantine it".to_string()
+    } else {
+        match master_hash.as_deref() {
+            Some(MasterCatalogue::EMBEDDED_SHA256) => format!("✅ matches {embedded}"),
+            Some(hash) => format!(
+                "ℹ️  installed from another source ({}); `mms master reset` installs {embedded}",
+                short(hash)
+            ),
+            None => format!("ℹ️  written by an older mms; `mms master reset` installs {embedded}"),
+        }
+    };
+    println!("Master:     {} {master_status}", master_path.display());
+
+    // Status only looks: nothing is migrated, prompted for or run on the way.
+    let peek = |path: &Path| -> Result<McpCatalogue, AppError> {
+        Ok(migration::parse_catalogue(&std::fs::read_to_string(path)?)?.0)
+    };
+    let vars = WithoutSecrets(ctx.vars()?);
+    let global = if global_path.exists() { Some(peek(&global_path)?) } else { None };
+    let global_status = match (&global, master_hash.as_deref()) {
+        (None, _) => "ℹ️  not created yet; the next command that needs it creates it".to_string(),
+        (Some(global), Some(expected)) => match global.meta.master_hash.as_deref() {
+            Some(hash) if hash == expected => "✅ up to date with the master".to_string(),
+            Some(hash) => format!(
+                "⚠️  generated from master {} but the master is now {}; run `mms global upgrade`",
+                short(hash),
+                short(expected)
+            ),
+            None => {
+                "ℹ️  no record of the master it came from; run `mms global upgrade`".to_string()
+            }
+        },
+        (Some(_), None) => "ℹ️  cannot tell; the master's origin is unknown".to_string(),
+    };
+    println!("Global:     {} {global_status}", global_path.display());
+
+    let Some(local_path) = &local_path else {
+        return Ok(());
+    };
+    let local = peek(local_path)?;
+    let stack = match global {
+        Some(global) => {
+            Some(CatalogueStack::over(global, &ctx.paths, ctx.settings()?, &vars)?.catalogue)
+        }
+        None => None,
+    };
+    if !local.meta.copied_from_global.is_empty() {
+        let stale: Vec<&str> = local
+            .meta
+            .copied_from_global
+            .iter()
+            .filter(|(name, fingerprint)| {
+                stack
+                    .as_ref()
+                    .and_then(|stack| stack.mcp_servers.get(*name))
+                    .is_none_or(|server| server.fingerprint() != **fingerprint)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if stale.is_empty() {
+            println!(
+                "Copies:     ✅ {} copied entr{} unchanged at the source",
+                local.meta.copied_from_global.len(),
+                if local.meta.copied_from_global.len() == 1 { "y" } else { "ies" }
+            );
+        } else {
+            println!("Copies:     ⚠️  changed at the source since copied: {}", stale.join(", "));
+        }
+    }
+
+    let resolved = match (&stack, local.has_references()) {
+        (_, false) => Some(local),
+        (Some(stack), true) => LocalCatalogue::resolve(&local, stack).ok(),
+        (None, true) => None,
+    };
+    let missing = resolved
+        .as_ref()
+        .map(|catalogue| missing::find_missing(&catalogue.mcp_servers, &vars))
+        .unwrap_or_default();
+    let has_providers = resolved.iter().flat_map(|c| c.mcp_servers.values()).any(|server| {
+        placeholder::server_placeholders(server)
+            .iter()
+            .any(|(_, segment)| matches!(segment, Segment::Provider { .. }))
+    });
+    let native = ctx.settings()?.placeholders == PlaceholderMode::Native;
+    let expected = |target: Target| -> Option<McpCatalogue> {
+        let blocked = missing.iter().any(|variable| variable.required);
+        let resolved = resolved.as_ref().filter(|_| !blocked && !has_providers)?;
+        if native {
+            return native::translate(resolved, target, &vars).ok().map(|(c, _)| c);
+        }
+        let mut expanded = resolved.clone();
+        placeholder::expand_catalogue(&mut expanded, &vars).ok().map(|_| expanded)
+    };
+    let describe = |current: Option<bool>, absent: &str| match current {
+        Some(true) => "✅ current".to_string(),
+        Some(false) => "⚠️  out of date; run `mms sync`".to_string(),
+        None => format!("ℹ️  {absent}"),
+    };
+    let unknown = if resolved.is_none() {
+        "❔ cannot tell; linked entries need the global catalogue"
+    } else if has_providers {
+        "❔ cannot tell without running provider commands"
+    } else {
+        "❔ cannot tell until the missing variables are set"
+    };
+    println!("Targets:");
+    let gemini_path = workspace.join(".gemini").join("settings.json");
+    let gemini = match expected(Target::Gemini) {
+        Some(catalogue) => {
+            describe(GeminiSync::is_current(workspace, &catalogue)?, "not synced yet")
+        }
+        None => unknown.to_string(),
+    };
+    println!("  Gemini  {} {gemini}", gemini_path.display());
+    let codex = match expected(Target::Codex) {
+        Some(catalogue) => {
+            describe(CodexSync::is_current(&ctx.paths, &catalogue)?, "not configured; skipped")
+        }
+        None => unknown.to_string(),
+    };
+    println!("  Codex   {} {codex}", ctx.paths.codex_config_path().display());
+
+    if missing.is_empty() {
+        println!("Variables:  ✅ all required variables are available");
+    } else {
+        println!("Variables:  ⚠️  missing values");
+        for variable in &missing {
+            println!("  - {} (needed by {})", variable.name, variable.servers.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Name, kind, status and whether it is missing, for a variable or provider placeholder.
+///
+/// Without `unlock` the secret store is left locked.
+fn describe_segment(
+    segment: &Segment,
+    vars: &Variables,
+    unlock: bool,
+    show_secrets: bool,
+) -> Option<(String, String, String, bool)> {
+    match segment {
+        Segment::Variable { name, fallback, .. } => {
+            let kind = match fallback {
+                Fallback::Keep => "required".to_string(),
+                Fallback::Default(default) => format!("optional (default \"{default}\")"),
+                Fallback::Required(_) => "required (sync fails without it)".to_string(),
+            };
+            let found = match unlock {
+                true => vars.resolve(name),
+                false => vars.resolve_unlocked(name),
+            };
+            Some(match found {
+                Some((value, origin)) => (
+                    name.clone(),
+                    kind,
+                    format!(
+                        "✓ {} from {origin}",
+                        if show_secrets { value.as_str() } else { redact::MASK }
+                    ),
+                    false,
+                ),
+                None => {
+                    let is_required = !matches!(fallback, Fallback::Default(_));
+                    let mut status =
+                        if is_required { "✗ not set" } else { "– not set" }.to_string();
+                    if !unlock && vars.has_secret_store() {
+                        status.push_str(" (secret store not checked)");
+                    }
+                    (name.clone(), kind, status, is_required)
+                }
+            })
+        }
+        Segment::Provider { provider, .. } => Some(match vars.provider_source(provider) {
+            Some(source) => {
+                (provider.to_string(), "provider".to_string(), format!("✓ {source}"), false)
+            }
+            None => {
+                (provider.to_string(), "provider".to_string(), "✗ not configured".to_string(), true)
+            }
+        }),
+        Segment::Literal(_) => None,
+    }
+}
+
+/// Print rows as indented, aligned columns; the last column is not padded.
+fn print_rows<const N: usize>(rows: &[[String; N]]) {
+    let widths: Vec<usize> = (0..N)
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in rows {
+        let mut line = String::from(" ");
+        for (column, cell) in row.iter().enumerate() {
+            if column + 1 == N {
+                line.push_str(&format!(" {cell}"));
+            } else {
+                line.push_str(&format!(" {cell:<width$} ", width = widths[column]));
+            }
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
+fn schema(kind: SchemaKind) -> Result<(), AppError> {
+    let schema = config::schema::catalogue_schema(kind);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+fn migrate(check: bool, paths: Vec<PathBuf>, ctx: &CommandContext) -> Result<(), AppError> {
+    let targets = if paths.is_empty() {
+        let mut defaults: Vec<PathBuf> =
+            LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path())
+                .into_iter()
+                .collect();
+        defaults.push(ctx.paths.global_catalogue_path());
+        defaults.into_iter().filter(|path| path.exists()).collect()
+    } else {
+        paths
+    };
+    // The master is stored as published and signed; only `master update`/`reset` replace it.
+    let master = ctx.paths.master_catalogue_path();
+    if targets.iter().any(|path| path == &master) {
+        return Err(AppError::config(format!(
+            "{} is never rewritten; run `mms master reset` or `mms master update` instead",
+            master.display()
+        )));
+    }
+
+    let mut outdated = 0;
+    let mut migrated = 0;
+    for path in &targets {
+        match migration::check_file(path)? {
+            MigrationStatus::Current => {
+                ctx.log(&format!("{} is up to date", path.display()));
+            }
+            MigrationStatus::Outdated(version) if check => {
+                println!("⚠️  {} uses schema version {version}", path.display());
+                outdated += 1;
+            }
+            MigrationStatus::Outdated(version) => {
+                migration::migrate_file(path)?;
+                println!(
+                    "✅ Migrated {} from schema version {version} to {}",
+                    path.display(),
+                    config::model::CURRENT_SCHEMA_VERSION
+                );
+                migrated += 1;
+            }
+        }
+    }
+
+    if check && outdated > 0 {
+        return Err(AppError::config(format!("{outdated} catalogue file(s) need migration")));
+    }
+    if outdated == 0 && migrated == 0 {
+        println!("ℹ️  All catalogues use the current schema");
+    }
+    Ok(())
+}
+
+fn upgrade_global(interactive: bool, dry_run: bool, ctx: &CommandContext) -> Result<(), AppError> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let global_path = ctx.paths.global_catalogue_path();
+    if !global_path.exists() {
+        return Err(AppError::config(format!(
+            "{} does not exist yet; any command that needs it will create it from the master",
+            global_path.display()
+        )));
+    }
+    if interactive && !std::io::stdin().is_terminal() {
+        return Err(AppError::config("--interactive needs a terminal"));
+    }
+    if GlobalCatalogue::load_base(&ctx.paths)?.is_none() {
+        println!(
+            "⚠️  No record of the master {} was generated from; every difference is a conflict",
+            global_path.display()
+        );
+    }
+
+    let (mut outcome, master) = GlobalCatalogue::plan_upgrade(
+        &ctx.paths,
+        ctx.settings()?.placeholders,
+        ctx.vars()?,
+        ctx.recovery,
+    )?;
+    let redactor = ctx.redactor(Some(&outcome.merged));
+    let show = |value: &Option<serde_json::Value>| match value {
+        Some(value) => redactor.text(&value.to_string()),
+        None => "(absent)".to_string(),
+    };
+
+    if outcome.applied.is_empty() && outcome.conflicts.is_empty() {
+        println!("ℹ️  {} already includes every master change", global_path.display());
+    }
+    for change in &outcome.applied {
+        println!("  {change}");
+    }
+    let mut kept = Vec::new();
+    for conflict in std::mem::take(&mut outcome.conflicts) {
+        println!("⚠️  Conflict in '{}' {}", conflict.server, conflict.field);
+        println!("    base:   {}", show(&conflict.base));
+        println!("    local:  {}", show(&conflict.mine));
+        println!("    master: {}", show(&conflict.theirs));
+        let take_master = interactive && {
+            print!("    Keep [l]ocal or take [m]aster? [l]: ");
+            std::io::stdout().flush()?;
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            matches!(answer.trim(), "m" | "master")
+        };
+        if take_master {
+            merge::take_theirs(&mut outcome.merged, &conflict)?;
+        } else {
+            kept.push(format!("'{}' {}", conflict.server, conflict.field));
+        }
+    }
+
+    if dry_run {
+        println!("ℹ️  Dry run; {} not modified", global_path.display());
+        return Ok(());
+    }
+    GlobalCatalogue::finish_upgrade(&ctx.paths, &outcome.merged, &master)?;
+    println!("✅ Upgraded {}", global_path.display());
+    if !kept.is_empty() {
+        println!("⚠️  Kept local values for {} conflict(s): {}", kept.len(), kept.join(", "));
+    }
+    Ok(())
+}
+
+fn master(action: MasterAction, ctx: &CommandContext) -> Result<(), AppError> {
+    let master_path = ctx.paths.master_catalogue_path();
+    if !master_path.exists() && MasterCatalogue::quarantined(&ctx.paths)?.is_none() {
+        MasterCatalogue::write_embedded(&ctx.paths)?;
+    }
+    // An unreadable master compares as empty; installing over it quarantines it first.
+    let current = MasterCatalogue::read_installed(&ctx.paths).unwrap_or_else(McpCatalogue::empty);
+    let (source, contents, signature, allow_unsigned, candidate, dry_run) = match action {
+        MasterAction::Update { source, sha256, dry_run, allow_unsigned } => {
+            let contents = MasterCatalogue::fetch(&source)?;
+            let signature = MasterCatalogue::fetch(&format!("{source}{SIGNATURE_SUFFIX}")).ok();
+            signature::check(
+                &ctx.paths,
+                &source,
+                contents.as_bytes(),
+                signature.as_deref(),
+                allow_unsigned,
+            )?;
+            let candidate =
+                MasterCatalogue::parse_candidate(&source, &contents, sha256.as_deref())?;
+            (source, contents, signature, allow_unsigned, candidate, dry_run)
+        }
+        MasterAction::Reset => {
+            let embedded = MasterCatalogue::EMBEDDED_JSON.to_string();
+            let candidate = MasterCatalogue::embedded()?;
+            ("the embedded catalogue".to_string(), embedded, None, false, candidate, false)
+        }
+    };
+    let install =
+        || MasterCatalogue::install(&ctx.paths, &contents, signature.as_deref(), allow_unsigned);
+
+    let changes = config::master::diff(&current, &candidate);
+    if changes.is_empty() {
+        if !dry_run && current.meta.master_hash != candidate.meta.master_hash {
+            install()?;
+        }
+        println!("ℹ️  {} already matches {source}", master_path.display());
+        return Ok(());
+    }
+    println!("Changes to {}:", master_path.display());
+    print_changes(&changes);
+    if dry_run {
+        println!("ℹ️  Dry run; nothing installed");
+        return Ok(());
+    }
+
+    install()?;
+    println!("✅ Installed {source} as {}", master_path.display());
+    println!(
+        "ℹ️  {} is unchanged; servers from the new master are used when it is recreated",
+        ctx.paths.global_catalogue_path().display()
+    );
+    Ok(())
+}
+
+fn import(
+    source: PathBuf,
+    into: Option<PathBuf>,
+    dry_run: bool,
+    ctx: &CommandContext,
+) -> Result<(), AppError> {
+    let entries = registry::load(&source)?;
+    if entries.is_empty() {
+        println!("No registry servers found in {}", source.display());
+        return Ok(());
+    }
+    if dry_run {
+        let servers: BTreeMap<_, _> = entries.into_iter().collect();
+        println!("{}", serde_json::to_string_pretty(&servers)?);
+        return Ok(());
+    }
+
+    let (mut catalogue, path) = match into {
+        Some(path) if path.exists() => (migration::read_catalogue(&path)?, path),
+        Some(path) => (McpCatalogue::empty(), path),
+        None => LocalCatalogue::load(&ctx.start_dir, &ctx.paths.global_catalogue_path())?,
+    };
+    for (name, server) in entries {
+        let replaced = catalogue.mcp_servers.insert(name.clone(), server).is_some();
+        catalogue.mark_resolved(&name, false);
+        catalogue.mark_copied(&name, None);
+        if replaced {
+            println!("🔄 Replaced '{name}' in {}", path.display());
+        } else {
+            println!("✅ Imported '{name}' into {}", path.display());
+        }
+    }
+    migration::write_catalogue(&path, &catalogue)
+}
+
+fn trust(names: Vec<String>, ctx: &CommandContext) -> Result<(), AppError> {
+    let mut stack = catalogue_stack(ctx, true)?;
+    if let Some(path) = LocalCatalogue::discover(&ctx.start_dir, &ctx.paths.global_catalogue_path())
+    {
+        // Approve what sync launches: project entries with their pinned package versions.
+        let workspace = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        apply_lock(&mut stack.catalogue, &workspace, ctx)?;
+    }
+    let mut definitions = Vec::new();
+    for name in names {
+        let server = stack.catalogue.mcp_servers.get(&name).ok_or_else(|| {
+            AppError::config(format!(
+                "MCP server '{name}' not found in the project, user, team or master catalogues"
+            ))
+        })?;
+        definitions.push((name, Definition::of(server)));
+    }
+
+    let mut store = TrustStore::load(&ctx.paths)?;
+    let redactor = ctx.redactor(Some(&stack.catalogue));
+    for (name, current) in definitions {
+        if store.is_approved(&name, &current) {
+            println!("ℹ️  The current definition of '{name}' is already approved");
+            continue;
+        }
+        let approved = store.latest(&name).cloned();
+        match &approved {
+            Some(_) => println!("Changes to '{name}' since it was last approved:"),
+            None => println!("Definition of '{name}':"),
+        }
+        for line in trust::diff(approved.as_ref().unwrap_or(&current), &current) {
+            println!("{}", redactor.text(&line));
+        }
+        store.approve(&name, &current);
+        println!("✅ Approved '{name}'");
+    }
+    store.save(&ctx.paths)
+}
+
+fn print_changes(changes: &[ServerChange]) {
+    for change in changes {
+        match change {
+            ServerChange::Added(name) => println!("  + {name}"),
+            ServerChange::Removed(name) => println!("  - {name}"),
+            ServerChange::Changed(name, fields) => println!("  ~ {name} ({})", fields.join(", ")),
+        }
+    }
+}
+
+fn catalogue(action: CatalogueAction, ctx: &CommandContext) -> Result<(), AppError> {
+    match action {
+        CatalogueAction::Subscribe { source, git_ref, name, allow_unsigned } => {
+            let (team, catalogue) = sources::subscribe(
+                &ctx.paths,
+                ctx.settings()?,
+                &source,
+                git_ref.as_deref(),
+                name.as_deref(),
+                allow_unsigned,
+            )?;
+            let revision = sources::revision(&team.path).unwrap_or_default();
+            println!("✅ Subscribed to {source} as team:{} at {revision}", team.name);
+            let names: Vec<&str> = catalogue.mcp_servers.keys().map(String::as_str).collect();
+            println!("   {} server(s): {}", names.len(), names.join(", "));
+        }
+        CatalogueAction::Pull { name, allow_unsigned } => {
+            let subscriptions: Vec<_> = ctx
+                .settings()?
+                .teams
+                .iter()
+                .filter(|team| team.url.is_some())
+                .filter(|team| name.as_ref().is_none_or(|name| team.name == *name))
+                .collect();
+            if subscriptions.is_empty() {
+                return match name {
+                    Some(name) => {
+                        Err(AppError::config(format!("No subscribed catalogue named '{name}'")))
+                    }
+                    None => {
+                        println!(
+                            "ℹ️  No subscribed catalogues; add one with `mms catalogue subscribe`"
+                        );
+                        Ok(())
+                    }
+                };
+            }
+            let mut failed = Vec::new();
+            for team in subscriptions {
+                match sources::pull(&ctx.paths, team, allow_unsigned) {
+                    Ok(pulled) if pulled.before == pulled.after => {
+                        println!("ℹ️  team:{} is up to date at {}", team.name, pulled.after)
+                    }
+                    Ok(pulled) => {
+                        println!("✅ team:{} {} → {}", team.name, pulled.before, pulled.after);
+                        print_changes(&pulled.changes);
+                    }
+                    Err(err) => {
+                        eprintln!("❌ team:{}: {err}", team.name);
+                        failed.push(team.name.clone());
+                    }
+                }
+            }
+            if !failed.is_empty() {
+                return Err(AppError::config(format!("Could not pull {}", failed.join(", "))));
+            }
+        }
+        CatalogueAction::List => {
+            if ctx.settings()?.teams.is_empty() {
+                println!("ℹ️  No team catalogues configured");
+                return Ok(());
+            }
+            for team in &ctx.settings()?.teams {
+                let servers = match team.load(&ctx.paths) {
+                    Ok(catalogue) => format!("{} server(s)", catalogue.mcp_servers.len()),
+                    Err(err) => format!("⚠️  {err}"),
+                };
+                println!("team:{}  {}  {servers}", team.name, team.catalogue_path().display());
+                if let Some(url) = &team.url {
+                    let git_ref = team.git_ref.as_deref().unwrap_or("default branch");
+                    let revision = sources::revision(&team.path).unwrap_or_else(|| "?".into());
+                    println!("  from {url} ({git_ref}) at {revision}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn secret(
+    action: SecretAction,
+    key_file: Option<PathBuf>,
+    ctx: &CommandContext,
+) -> Result<(), AppError> {
+    let store = SecretStore::open(&ctx.paths, ctx.settings()?, key_file.as_deref());
+    let missing = |name: &str| AppError::config(format!("No secret named '{name}' in the store"));
+
+    match action {
+        SecretAction::Set { name, value } => {
+            if !secret_store::is_secret_name(&name) {
+                return Err(AppError::config(format!(
+                    "invalid secret name '{name}': use letters, digits, '_', '-' or '.'"
+                )));
+            }
+            let mut secrets = store.read()?;
+            let value = match value {
+                Some(value) => value,
+                None => read_secret_value(&name)?,
+            };
+            secrets.insert(name.clone(), value);
+            store.write(&secrets)?;
+            println!("✅ Stored secret '{name}'");
+        }
+        SecretAction::Get { name } => {
+            let secrets = store.read()?;
+            println!("{}", secrets.get(&name).ok_or_else(|| missing(&name))?);
+        }
+        SecretAction::List => {
+            let secrets = store.read()?;
+            if secrets.is_empty() {
+                println!("No secrets stored in {}", ctx.paths.secret_store_path().display());
+            }
+            for name in secrets.keys() {
+                println!("{name}");
+            }
+        }
+        SecretAction::Rm { name } => {
+            let mut secrets = store.read()?;
+            secrets.remove(&name).ok_or_else(|| missing(&name))?;
+            store.write(&secrets)?;
+            println!("🗑️  Removed secret '{name}'");
+        }
+    }
+    Ok(())
+}
+
+fn read_secret_value(name: &str) -> Result<String, AppError> {
+    use std::io::{BufRead, IsTerminal};
+
+    let value = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("Value for '{name}': "))?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        line.trim_end_matches(['\n', '\r']).to_string()
+    };
+    if value.is_empty() {
+        return Err(AppError::config(format!("no value given for secret '{name}'")));
+    }
+    Ok(value)
+}
+
 fn clean(selection: CleanSelection, ctx: &CommandContext) -> Result<(), AppError> {
     let mut operations = Vec::new();
 
@@ -203,6 +1445,10 @@ fn clean(selection: CleanSelection, ctx: &CommandContext) -> Result<(), AppError
 
     if selection.master {
         operations.push(("CLI master catalogue", ctx.paths.master_catalogue_path()));
+        let marker = ctx.paths.quarantine_marker_path();
+        if marker.exists() {
+            operations.push(("master quarantine marker", marker));
+        }
     }
 
     if selection.global {
@@ -1,8 +1,10 @@
 use clap::Parser;
 use mms::cli::Cli;
-use mms::config::MmsPaths;
+use mms::config::master::Recovery;
+use mms::config::{MasterCatalogue, MmsPaths};
 use mms::error::AppError;
 use mms::CommandContext;
+use std::io::{BufRead, IsTerminal, Write};
 
 fn main() {
     if let Err(err) = run() {
@@ -15,6 +17,29 @@ fn run() -> Result<(), AppError> {
     let cli = Cli::parse();
     let paths = MmsPaths::new()?;
     let start_dir = std::env::current_dir()?;
-    let context = CommandContext { paths, start_dir, verbose: cli.verbose };
+    let recovery = if cli.repair { Recovery::Repair } else { ask_recovery(&paths)? };
+    let context = CommandContext::new(
+        paths,
+        start_dir,
+        cli.verbose,
+        cli.show_secrets,
+        recovery,
+        cli.env_files,
+    );
     mms::execute(cli.command, context)
 }
+
+/// Offer to restore the embedded master when the stored one is unreadable or quarantined.
+fn ask_recovery(paths: &MmsPaths) -> Result<Recovery, AppError> {
+    if !MasterCatalogue::needs_recovery(paths) || !std::io::stdin().is_terminal() {
+        return Ok(Recovery::Refuse);
+    }
+    eprint!(
+        "The master catalogue cannot be read. Restore the embedded copy? \
+         The unreadable copy is kept. [y/N]: "
+    );
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(if matches!(answer.trim(), "y" | "Y" | "yes") { Recovery::Repair } else { Recovery::Refuse })
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(status.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&status.stderr));
+}
+
+fn commit_catalogue(work: &Path, catalogue: serde_json::Value) {
+    let contents = catalogue.to_string();
+    std::fs::write(work.join(".mcp.json"), &contents).unwrap();
+    std::fs::write(work.join(".mcp.json.minisig"), common::minisign(contents.as_bytes())).unwrap();
+    git(work, &["add", ".mcp.json", ".mcp.json.minisig"]);
+    git(work, &["commit", "--quiet", "-m", "Update catalogue"]);
+    git(work, &["push", "--quiet", "origin", "main"]);
+}
+
+/// A bare repository holding a team catalogue, plus a working copy to push changes from.
+fn team_repo(ctx: &TestContext) -> (std::path::PathBuf, std::path::PathBuf) {
+    let remote = ctx.home().join("acme-servers.git");
+    let work = ctx.create_workspace("acme-work");
+    git(ctx.home(), &["init", "--quiet", "--bare", "-b", "main", remote.to_str().unwrap()]);
+    git(&work, &["init", "--quiet", "-b", "main"]);
+    git(&work, &["remote", "add", "origin", remote.to_str().unwrap()]);
+    commit_catalogue(
+        &work,
+        serde_json::json!({ "mcpServers": { "internal": { "command": "acme-internal" } } }),
+    );
+    (remote, work)
+}
+
+#[test]
+fn subscribe_pull_and_list_a_git_catalogue() {
+    let ctx = TestContext::new();
+    ctx.trust_test_key();
+    let (remote, work) = team_repo(&ctx);
+
+    ctx.cli()
+        .args(["catalogue", "subscribe", remote.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Subscribed to"))
+        .stdout(predicate::str::contains("team:acme-servers"))
+        .stdout(predicate::str::contains("1 server(s): internal"));
+    let settings = std::fs::read_to_string(ctx.home().join(".config/mms/config.toml")).unwrap();
+    assert!(settings.contains("path = \"sources/acme-servers\""));
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[internal] (team:acme-servers)"));
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "internal"]).assert().success();
+
+    commit_catalogue(
+        &work,
+        serde_json::json!({ "mcpServers": {
+            "internal": { "command": "acme-internal", "args": ["--v2"] },
+            "billing": { "command": "acme-billing" }
+        } }),
+    );
+    ctx.cli()
+        .args(["catalogue", "pull"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ billing"))
+        .stdout(predicate::str::contains("~ internal (args)"));
+    ctx.cli()
+        .args(["catalogue", "pull"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+
+    // A catalogue whose signature no longer matches is refused.
+    std::fs::write(
+        work.join(".mcp.json"),
+        r#"{ "mcpServers": { "internal": { "command": "evil" } } }"#,
+    )
+    .unwrap();
+    git(&work, &["commit", "--quiet", "-am", "Swap command"]);
+    git(&work, &["push", "--quiet", "origin", "main"]);
+    ctx.cli()
+        .args(["catalogue", "pull"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match its signature"));
+    ctx.cli_in(ctx.home())
+        .args(["command", "internal"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(team:acme-servers): acme-internal --v2"));
+
+    // An invalid catalogue is refused and the checkout stays on the last good revision.
+    commit_catalogue(&work, serde_json::json!({ "mcpServers": { "broken": { "args": [] } } }));
+    ctx.cli()
+        .args(["catalogue", "pull", "acme-servers"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed validation"));
+    ctx.cli()
+        .args(["catalogue", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("team:acme-servers"))
+        .stdout(predicate::str::contains("2 server(s)"))
+        .stdout(predicate::str::contains("(default branch)"));
+}
+
+#[test]
+fn pull_allows_unsigned_updates_for_one_run_only() {
+    let ctx = TestContext::new();
+    ctx.trust_test_key();
+    let (remote, work) = team_repo(&ctx);
+    ctx.cli().args(["catalogue", "subscribe", remote.to_str().unwrap()]).assert().success();
+
+    let push_unsigned = |command: &str| {
+        let catalogue = serde_json::json!({ "mcpServers": { "internal": { "command": command } } });
+        std::fs::write(work.join(".mcp.json"), catalogue.to_string()).unwrap();
+        git(&work, &["rm", "--quiet", "--ignore-unmatch", ".mcp.json.minisig"]);
+        git(&work, &["commit", "--quiet", "-am", "Unsigned update"]);
+        git(&work, &["push", "--quiet", "origin", "main"]);
+    };
+    push_unsigned("acme-unsigned");
+    ctx.cli()
+        .args(["catalogue", "pull", "--allow-unsigned"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("applies to this pull only"));
+    let settings = std::fs::read_to_string(ctx.home().join(".config/mms/config.toml")).unwrap();
+    assert!(!settings.contains("allow_unsigned"));
+
+    push_unsigned("acme-tampered");
+    ctx.cli()
+        .args(["catalogue", "pull"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has no signature"));
+}
+
+#[test]
+fn subscribe_refuses_repositories_without_a_catalogue() {
+    let ctx = TestContext::new();
+    let (remote, work) = team_repo(&ctx);
+    std::fs::write(work.join("README.md"), "no catalogue here").unwrap();
+    git(&work, &["rm", "--quiet", ".mcp.json"]);
+    git(&work, &["add", "README.md"]);
+    git(&work, &["commit", "--quiet", "-m", "Drop catalogue"]);
+    git(&work, &["push", "--quiet", "origin", "main"]);
+
+    ctx.cli()
+        .args(["catalogue", "subscribe", remote.to_str().unwrap(), "--name", "acme"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has no .mcp.json"));
+    assert!(!ctx.home().join(".config/mms/sources/acme").exists());
+
+    // An older revision can still be followed explicitly, once its signer is trusted.
+    let subscribe_old = || {
+        let mut cmd = ctx.cli();
+        cmd.args(["catalogue", "subscribe", remote.to_str().unwrap()])
+            .args(["--name", "acme", "--ref", "main~1"]);
+        cmd
+    };
+    subscribe_old().assert().failure().stderr(predicate::str::contains("lists no keys"));
+    ctx.trust_test_key();
+    subscribe_old().assert().success().stdout(predicate::str::contains("signed by trusted key"));
+}
+
+#[test]
+fn sources_and_refs_are_never_git_options() {
+    let ctx = TestContext::new();
+    ctx.trust_test_key();
+    let marker = ctx.home().join("ran");
+    let source = format!("--upload-pack=touch {}", marker.display());
+    ctx.cli()
+        .args(["catalogue", "subscribe", "--name", "evil", "--", &source])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("git clone failed"));
+    assert!(!marker.exists());
+
+    let (remote, _) = team_repo(&ctx);
+    ctx.cli()
+        .args(["catalogue", "subscribe", remote.to_str().unwrap(), "--ref=--orphan=x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'--orphan=x' is not a branch, tag or commit"));
+    assert!(!ctx.home().join(".config/mms/sources/acme-servers").exists());
+}
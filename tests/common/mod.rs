@@ -70,10 +70,62 @@ impl TestContext {
         self.home().join(".config").join("mms").join("master.json")
     }
 
+    /// Write `~/.config/mms/config.toml` inside the sandbox.
+    pub fn write_settings(&self, contents: &str) {
+        let dir = self.home().join(".config").join("mms");
+        fs::create_dir_all(&dir).expect("Failed to create mms config directory");
+        fs::write(dir.join("config.toml"), contents).expect("Failed to write mms settings");
+    }
+
     /// Path to the local `.mcp.json` under the default workspace.
     pub fn local_mcp_path(&self) -> PathBuf {
         self.work_dir().join(".mcp.json")
     }
+
+    /// Trust the test publisher key used by [`minisign`].
+    pub fn trust_test_key(&self) {
+        let dir = self.home().join(".config").join("mms");
+        fs::create_dir_all(&dir).expect("Failed to create mms config directory");
+        let contents = format!("untrusted comment: test key\n{}\n", test_key().0);
+        fs::write(dir.join("trusted_keys"), contents).expect("Failed to write trusted keys");
+    }
+
+    /// Trust the test key and sign `path` with it, as a master or team catalogue publisher.
+    pub fn sign(&self, path: &Path) {
+        self.trust_test_key();
+        let signature = minisign(&fs::read(path).expect("Failed to read catalogue"));
+        fs::write(format!("{}.minisig", path.display()), signature)
+            .expect("Failed to write signature");
+    }
+}
+
+/// The test publisher's minisign public key line and signing key.
+fn test_key() -> (String, ed25519_dalek::SigningKey) {
+    use base64::Engine;
+    let key = ed25519_dalek::SigningKey::from_bytes(&[42u8; 32]);
+    let public = [b"Ed".as_slice(), &TEST_KEY_ID, key.verifying_key().as_bytes()].concat();
+    (base64::engine::general_purpose::STANDARD.encode(public), key)
+}
+
+const TEST_KEY_ID: [u8; 8] = *b"mmstest!";
+
+/// Sign `contents` like `minisign -S` would with the test publisher key.
+#[allow(dead_code)]
+pub fn minisign(contents: &[u8]) -> String {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use blake2::Digest;
+    use ed25519_dalek::Signer;
+
+    let (_, key) = test_key();
+    let signature = key.sign(&blake2::Blake2b512::digest(contents)).to_bytes();
+    let comment = "timestamp:1700000000\tfile:.mcp.json";
+    let global = key.sign(&[signature.as_slice(), comment.as_bytes()].concat()).to_bytes();
+    format!(
+        "untrusted comment: signature from mms test key\n{}\ntrusted comment: {comment}\n{}\n",
+        STANDARD.encode([b"ED".as_slice(), &TEST_KEY_ID, &signature].concat()),
+        STANDARD.encode(global)
+    )
 }
 
 impl Drop for TestContext {
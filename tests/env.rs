@@ -0,0 +1,201 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+fn github_token(ctx: &TestContext) -> String {
+    let global: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.global_mcp_path()).unwrap()).unwrap();
+    global["mcpServers"]["github"]["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn project_env_file_feeds_placeholders_and_reports_origin() {
+    let ctx = TestContext::new();
+    std::fs::write(ctx.work_dir().join(".mms.env"), "MMS_GITHUB_PAT=from-project\n").unwrap();
+
+    ctx.cli()
+        .args(["-v", "list"])
+        .env_remove("MMS_GITHUB_PAT")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("MMS_GITHUB_PAT ← "))
+        .stdout(predicate::str::contains(".mms.env"))
+        .stdout(predicate::str::contains("from-project").not());
+
+    assert_eq!(github_token(&ctx), "from-project");
+}
+
+#[test]
+fn env_file_flag_overrides_process_and_project_values() {
+    let ctx = TestContext::new();
+    std::fs::write(ctx.work_dir().join(".mms.env"), "MMS_GITHUB_PAT=from-project\n").unwrap();
+    let explicit = ctx.home().join("explicit.env");
+    std::fs::write(&explicit, "export MMS_GITHUB_PAT=\"from-flag\"\n").unwrap();
+
+    ctx.cli()
+        .arg("--env-file")
+        .arg(&explicit)
+        .arg("list")
+        .env("MMS_GITHUB_PAT", "from-process")
+        .assert()
+        .success();
+
+    assert_eq!(github_token(&ctx), "from-flag");
+}
+
+#[test]
+fn process_environment_beats_user_env_file() {
+    let ctx = TestContext::new();
+    let config_dir = ctx.home().join(".config").join("mms");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("env"), "MMS_GITHUB_PAT=from-user-file\n").unwrap();
+
+    ctx.cli().arg("list").env("MMS_GITHUB_PAT", "from-process").assert().success();
+
+    assert_eq!(github_token(&ctx), "from-process");
+}
+
+#[cfg(unix)]
+#[test]
+fn secret_providers_resolve_once_and_report_failures_per_server() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new();
+    let calls = ctx.home().join("calls.log");
+    let stub = ctx.home().join("fake-pass");
+    std::fs::write(
+        &stub,
+        format!("#!/bin/sh\necho \"$1\" >> {}\necho token-for-$1\n", calls.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+    ctx.write_settings(&format!(
+        "[placeholders]\nresolve = \"late\"\n\n[secrets]\ngithub = \"{} github\"\n",
+        stub.display()
+    ));
+
+    let catalogue = serde_json::json!({
+        "mcpServers": {
+            "one": { "command": "one", "env": { "TOKEN": "${secret:github}" } },
+            "two": { "command": "two", "args": ["--token=${secret:github}"] },
+            "broken": { "command": "three", "env": { "KEY": "${cmd:echo locked ghp_0123456789abcdefghij >&2; exit 1}" } }
+        }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MCP server 'broken' env.KEY"))
+        .stderr(predicate::str::contains("locked"))
+        .stderr(predicate::str::contains("ghp_0123456789abcdefghij").not());
+
+    std::fs::remove_file(&calls).unwrap();
+    let mut fixed = catalogue;
+    fixed["mcpServers"].as_object_mut().unwrap().remove("broken");
+    std::fs::write(ctx.local_mcp_path(), fixed.to_string()).unwrap();
+
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+
+    let settings =
+        std::fs::read_to_string(ctx.work_dir().join(".gemini").join("settings.json")).unwrap();
+    assert!(settings.contains("\"TOKEN\": \"token-for-github\""));
+    assert!(settings.contains("--token=token-for-github"));
+    assert_eq!(std::fs::read_to_string(&calls).unwrap().lines().count(), 1);
+}
+
+#[test]
+fn env_report_masks_values_and_fails_strictly_on_missing_variables() {
+    let ctx = TestContext::new();
+    let catalogue = serde_json::json!({
+        "mcpServers": {
+            "api": {
+                "command": "api-mcp",
+                "args": ["--level=${LOG_LEVEL:-info}"],
+                "env": { "TOKEN": "${API_TOKEN}", "REGION": "${API_REGION}" }
+            }
+        }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+    std::fs::write(ctx.work_dir().join(".mms.env"), "API_REGION=eu-central-secret-1\n").unwrap();
+
+    ctx.cli()
+        .arg("env")
+        .env_remove("API_TOKEN")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("API_REGION"))
+        .stdout(predicate::str::contains("✓ •••••• from"))
+        .stdout(predicate::str::contains("eu-central-secret-1").not())
+        .stdout(predicate::str::contains("optional (default \"info\")"))
+        .stdout(predicate::str::contains("${API_TOKEN} would be written unresolved"))
+        .stdout(predicate::str::contains("Missing required variables: API_TOKEN"));
+
+    ctx.cli()
+        .args(["env", "--strict"])
+        .env_remove("API_TOKEN")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Missing required variables: API_TOKEN"));
+
+    ctx.cli()
+        .args(["env", "--strict"])
+        .env("API_TOKEN", "t")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All required variables are available"));
+}
+
+#[test]
+fn non_interactive_add_and_sync_list_missing_variables() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+
+    // An unset plain `${VAR}` does not stop `add`; the placeholder is kept.
+    ctx.cli().args(["add", "github"]).env_remove("MMS_GITHUB_PAT").assert().success();
+    let local = std::fs::read_to_string(ctx.local_mcp_path()).unwrap();
+    assert!(local.contains("${MMS_GITHUB_PAT}"), "{local}");
+
+    let global_path = ctx.global_mcp_path();
+    let mut global: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&global_path).unwrap()).unwrap();
+    global["mcpServers"]["needy"] =
+        serde_json::json!({ "command": "needy", "env": { "K": "${NEEDED:?set it}" } });
+    std::fs::write(&global_path, global.to_string()).unwrap();
+    ctx.cli()
+        .args(["add", "needy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing values for placeholders"))
+        .stderr(predicate::str::contains("NEEDED (needed by needy)"));
+    let local = std::fs::read_to_string(ctx.local_mcp_path()).unwrap();
+    assert!(!local.contains("needy"));
+
+    let catalogue = serde_json::json!({
+        "mcpServers": {
+            "one": { "command": "one", "env": { "A": "${FIRST}", "B": "${SECOND:?needed}" } },
+            "two": { "command": "two", "args": ["${FIRST}", "${OPTIONAL:-x}"] }
+        }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SECOND (needed by one)"))
+        .stderr(predicate::str::contains("FIRST").not())
+        .stderr(predicate::str::contains("OPTIONAL").not());
+
+    // Plain `${VAR}` placeholders are left for the target to resolve.
+    std::fs::write(ctx.work_dir().join(".mms.env"), "SECOND=2\n").unwrap();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+    let settings =
+        std::fs::read_to_string(ctx.work_dir().join(".gemini").join("settings.json")).unwrap();
+    assert!(settings.contains("\"A\": \"${FIRST}\""), "{settings}");
+    assert!(settings.contains("\"B\": \"2\""), "{settings}");
+}
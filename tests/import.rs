@@ -0,0 +1,89 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+const WEATHER: &str = r#"{
+  "$schema": "https://static.modelcontextprotocol.io/schemas/2025-09-29/server.schema.json",
+  "name": "io.github.acme/weather",
+  "description": "Weather forecasts",
+  "version": "1.2.0",
+  "packages": [
+    {
+      "registryType": "npm",
+      "identifier": "@acme/weather-mcp",
+      "version": "1.2.0",
+      "transport": { "type": "stdio" },
+      "environmentVariables": [
+        { "name": "WEATHER_API_KEY", "isRequired": true, "isSecret": true }
+      ]
+    }
+  ]
+}"#;
+
+const SEARCH: &str = r#"{
+  "name": "io.github.acme/search",
+  "remotes": [
+    {
+      "type": "sse",
+      "url": "https://search.example.com/sse",
+      "headers": [{ "name": "X-Api-Key", "isSecret": true }]
+    }
+  ]
+}"#;
+
+#[test]
+fn import_registry_directory_into_project_catalogue() {
+    let ctx = TestContext::new();
+    let registry = ctx.create_workspace("registry");
+    std::fs::create_dir_all(registry.join("weather")).unwrap();
+    std::fs::write(registry.join("weather").join("server.json"), WEATHER).unwrap();
+    std::fs::write(registry.join("search.json"), SEARCH).unwrap();
+
+    ctx.cli().arg("init").assert().success();
+    ctx.cli()
+        .args(["import", "--registry", registry.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 'weather'"))
+        .stdout(predicate::str::contains("Imported 'search'"));
+
+    let local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.local_mcp_path()).unwrap()).unwrap();
+    let weather = &local["mcpServers"]["weather"];
+    assert_eq!(weather["command"], "npx");
+    assert_eq!(weather["args"], serde_json::json!(["-y", "@acme/weather-mcp@1.2.0"]));
+    assert_eq!(weather["env"]["WEATHER_API_KEY"], "${WEATHER_API_KEY}");
+    assert_eq!(weather["description"], "Weather forecasts");
+    let search = &local["mcpServers"]["search"];
+    assert_eq!(search["type"], "sse");
+    assert_eq!(search["url"], "https://search.example.com/sse");
+    assert_eq!(search["headers"]["X-Api-Key"], "${X_API_KEY}");
+
+    ctx.cli().arg("validate").assert().success();
+}
+
+#[test]
+fn import_dry_run_prints_entries_without_writing() {
+    let ctx = TestContext::new();
+    let file = ctx.home().join("server.json");
+    std::fs::write(&file, WEATHER).unwrap();
+    let target = ctx.home().join("catalogue.json");
+
+    ctx.cli()
+        .args(["import", "--registry", file.to_str().unwrap(), "--dry-run"])
+        .args(["--into", target.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"@acme/weather-mcp@1.2.0\""));
+    assert!(!target.exists());
+
+    ctx.cli()
+        .args(["import", "--registry", file.to_str().unwrap()])
+        .args(["--into", target.to_str().unwrap()])
+        .assert()
+        .success();
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&target).unwrap()).unwrap();
+    assert_eq!(written["mcpServers"]["weather"]["command"], "npx");
+}
@@ -22,10 +22,15 @@ fn init_from_global_populates_catalogue() {
 
     let local_content = std::fs::read_to_string(ctx.local_mcp_path()).expect("local file");
     let global_content = std::fs::read_to_string(ctx.global_mcp_path()).expect("global file");
-    assert_eq!(local_content, global_content);
-
     let json: serde_json::Value = serde_json::from_str(&local_content).expect("valid json");
+    let global: serde_json::Value = serde_json::from_str(&global_content).expect("valid json");
+    assert_eq!(json["mcpServers"], global["mcpServers"]);
     assert!(json["mcpServers"].as_object().unwrap().contains_key("context7"));
+
+    // The copy records where each entry came from rather than the master hash.
+    assert!(json["mms"]["copiedFromGlobal"]["context7"].is_string());
+    assert!(json["mms"].get("masterHash").is_none());
+    assert!(global["mms"]["masterHash"].is_string());
 }
 
 #[test]
@@ -38,3 +43,13 @@ fn version_flag_works() {
         .success()
         .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
 }
+
+#[test]
+fn global_catalogue_resolves_placeholders_from_environment() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").arg("--from-global").env("MMS_GITHUB_PAT", "ghp_test").assert().success();
+
+    let global: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.global_mcp_path()).unwrap()).unwrap();
+    assert_eq!(global["mcpServers"]["github"]["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_test");
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+fn write_team(ctx: &TestContext) -> std::path::PathBuf {
+    let checkout = ctx.create_workspace("acme-mcp");
+    std::fs::write(
+        checkout.join(".mcp.json"),
+        serde_json::json!({
+            "mcpServers": {
+                "internal": { "command": "acme-internal", "args": ["--stdio"] },
+                "context7": { "command": "npx", "args": ["context7@pinned"] },
+                "memory": { "command": "acme-memory" }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    ctx.write_settings(&format!("[[teams]]\nname = \"acme\"\npath = \"{}\"\n", checkout.display()));
+    ctx.sign(&checkout.join(".mcp.json"));
+    checkout
+}
+
+#[test]
+fn team_layers_sit_between_master_and_user() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("list").assert().success();
+    // A user edit in ~/.mcp.json outranks the team layer.
+    let mut global: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.global_mcp_path()).unwrap()).unwrap();
+    global["mcpServers"]["memory"]["args"] = serde_json::json!(["mine"]);
+    std::fs::write(ctx.global_mcp_path(), global.to_string()).unwrap();
+    write_team(&ctx);
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[internal] (team:acme)"))
+        .stdout(predicate::str::contains("[context7] (team:acme)\nnpx context7@pinned"))
+        .stdout(predicate::str::contains("[memory] (user)"))
+        .stdout(predicate::str::contains("[playwright] (master)"));
+
+    ctx.cli().args(["command", "internal"]).assert().success().stdout(predicate::str::contains(
+        "Command for 'internal' (team:acme): acme-internal --stdio",
+    ));
+
+    ctx.cli().arg("init").assert().success();
+    ctx.cli()
+        .args(["add", "internal"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added 'internal' from team:acme"));
+    let local = std::fs::read_to_string(ctx.local_mcp_path()).unwrap();
+    assert!(local.contains("acme-internal"));
+
+    // The project layer wins over everything below it.
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[internal] (project)"));
+}
+
+#[test]
+fn missing_team_checkout_is_skipped_with_a_warning() {
+    let ctx = TestContext::new();
+    ctx.write_settings("[[teams]]\npath = \"~/not-cloned\"\n");
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Skipping team catalogue 'not-cloned'"))
+        .stdout(predicate::str::contains("[context7] (master)"));
+}
+
+#[test]
+fn adding_team_entries_runs_no_providers() {
+    let ctx = TestContext::new();
+    let checkout = write_team(&ctx);
+    let marker = ctx.home().join("ran");
+    let team = serde_json::json!({ "mcpServers": { "scripted": { "command": "acme-scripted",
+        "env": { "TOKEN": format!("${{cmd:touch {}}}", marker.display()) } } } });
+    std::fs::write(checkout.join(".mcp.json"), team.to_string()).unwrap();
+    ctx.sign(&checkout.join(".mcp.json"));
+
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "scripted"]).assert().success();
+
+    assert!(!marker.exists());
+    assert!(std::fs::read_to_string(ctx.local_mcp_path()).unwrap().contains("${cmd:touch"));
+}
+
+#[test]
+fn unsigned_team_catalogues_are_refused_unless_allowed() {
+    let ctx = TestContext::new();
+    let checkout = write_team(&ctx);
+    std::fs::remove_file(checkout.join(".mcp.json.minisig")).unwrap();
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Skipping team catalogue 'acme'"))
+        .stderr(predicate::str::contains("has no signature"))
+        .stdout(predicate::str::contains("[internal]").not());
+
+    ctx.write_settings(&format!(
+        "[[teams]]\nname = \"acme\"\npath = \"{}\"\nallow_unsigned = true\n",
+        checkout.display()
+    ));
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("WARNING"))
+        .stdout(predicate::str::contains("[internal] (team:acme)"));
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use common::TestContext;
+use mms::config::lock::{LockOutcome, Lockfile, Resolver};
+use mms::config::migrate;
+use mms::error::AppError;
+use predicates::prelude::*;
+
+const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+/// Answers like the registries would, without network access.
+struct Registry {
+    context7: &'static str,
+}
+
+impl Resolver for Registry {
+    fn npm(&self, name: &str, requested: Option<&str>) -> Result<String, AppError> {
+        assert_eq!((name, requested), ("@upstash/context7-mcp", Some("latest")));
+        Ok(self.context7.to_string())
+    }
+
+    fn pypi(&self, name: &str, _: Option<&str>) -> Result<String, AppError> {
+        Err(AppError::Config(format!("unexpected PyPI lookup for {name}")))
+    }
+
+    fn git(&self, url: &str, git_ref: Option<&str>) -> Result<String, AppError> {
+        assert_eq!((url, git_ref), ("https://example.com/acme/tool", None));
+        Ok(COMMIT.to_string())
+    }
+}
+
+/// Lock the project catalogue the way `mms lock` does, returning the pinned arguments.
+fn lock(ctx: &TestContext, update: &[&str], context7: &'static str) -> Vec<String> {
+    let contents = std::fs::read_to_string(ctx.local_mcp_path()).unwrap();
+    let (catalogue, _) = migrate::parse_catalogue(&contents).unwrap();
+    let mut lockfile = Lockfile::load(ctx.work_dir()).unwrap().unwrap_or_default();
+    let update: Vec<String> = update.iter().map(|name| name.to_string()).collect();
+    let outcomes = lockfile.lock(&catalogue, &update, &Registry { context7 }).unwrap();
+    lockfile.save(ctx.work_dir()).unwrap();
+    outcomes
+        .into_iter()
+        .filter_map(|outcome| match outcome {
+            LockOutcome::Pinned { arg, .. } => Some(arg),
+            _ => None,
+        })
+        .collect()
+}
+
+fn gemini_args(ctx: &TestContext, name: &str) -> serde_json::Value {
+    let settings = ctx.work_dir().join(".gemini").join("settings.json");
+    let settings: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(settings).unwrap()).unwrap();
+    settings["mcpServers"][name]["args"].clone()
+}
+
+#[test]
+fn lock_pins_floating_packages_for_sync() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    let path = ctx.local_mcp_path();
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    local["mcpServers"]["tool"] = serde_json::json!({
+        "command": "uvx",
+        "args": ["--from", "git+https://example.com/acme/tool", "tool"]
+    });
+    std::fs::write(&path, serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    assert_eq!(
+        lock(&ctx, &[], "1.0.6"),
+        [
+            "@upstash/context7-mcp@1.0.6".to_string(),
+            format!("git+https://example.com/acme/tool@{COMMIT}")
+        ]
+    );
+    let lockfile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.work_dir().join(".mcp.lock")).unwrap())
+            .unwrap();
+    assert_eq!(lockfile["servers"]["context7"]["package"], "@upstash/context7-mcp@latest");
+    assert_eq!(lockfile["servers"]["context7"]["version"], "1.0.6");
+
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+    assert_eq!(
+        gemini_args(&ctx, "context7"),
+        serde_json::json!(["-y", "@upstash/context7-mcp@1.0.6"])
+    );
+    assert_eq!(gemini_args(&ctx, "tool")[1], format!("git+https://example.com/acme/tool@{COMMIT}"));
+    // The catalogue itself keeps the floating spec.
+    assert!(std::fs::read_to_string(&path).unwrap().contains("@upstash/context7-mcp@latest"));
+
+    // A newer release is only picked up when asked for.
+    assert!(lock(&ctx, &[], "1.0.7").is_empty());
+    assert_eq!(lock(&ctx, &["context7"], "1.0.7"), ["@upstash/context7-mcp@1.0.7"]);
+    // A new pin launches different code, so it needs approval like any other change.
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("+ args: -y @upstash/context7-mcp@1.0.7"));
+    ctx.cli().args(["trust", "context7"]).assert().success();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+    assert_eq!(gemini_args(&ctx, "context7")[1], "@upstash/context7-mcp@1.0.7");
+
+    ctx.cli()
+        .args(["lock", "--update", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MCP server 'nope' is not in"));
+}
+
+#[test]
+fn stale_lock_entries_are_reported_and_ignored() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    lock(&ctx, &[], "1.0.6");
+
+    let path = ctx.local_mcp_path();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(
+        &path,
+        contents.replace("@upstash/context7-mcp@latest", "@upstash/context7-mcp@next"),
+    )
+    .unwrap();
+    ctx.cli().args(["trust", "context7"]).assert().success();
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("entry for 'context7' no longer matches"));
+    assert_eq!(gemini_args(&ctx, "context7")[1], "@upstash/context7-mcp@next");
+}
+
+#[test]
+fn malformed_lock_versions_are_refused() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    let lockfile = serde_json::json!({ "lockVersion": 1, "servers": { "context7": {
+        "package": "@upstash/context7-mcp@latest", "version": "1.0.6 --registry=evil" } } });
+    std::fs::write(ctx.work_dir().join(".mcp.lock"), lockfile.to_string()).unwrap();
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a version number or commit SHA"));
+}
+
+#[test]
+fn lock_reports_every_failure_and_keeps_the_other_pins() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    lock(&ctx, &[], "1.0.6");
+
+    // PyPI constraints cannot be resolved, which needs no network to find out.
+    let path = ctx.local_mcp_path();
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    local["mcpServers"]["alpha"] = serde_json::json!({ "command": "uvx", "args": ["alpha>=1"] });
+    local["mcpServers"]["beta"] = serde_json::json!({ "command": "uvx", "args": ["beta~=2.0"] });
+    std::fs::write(&path, serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    ctx.cli()
+        .arg("lock")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Wrote"))
+        .stderr(predicate::str::contains("could not pin 2 server(s)"))
+        .stderr(predicate::str::contains("'alpha' (alpha>=1: "))
+        .stderr(predicate::str::contains("'beta' (beta~=2.0: "));
+    let lockfile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.work_dir().join(".mcp.lock")).unwrap())
+            .unwrap();
+    assert_eq!(lockfile["servers"]["context7"]["version"], "1.0.6");
+    assert!(lockfile["servers"].get("alpha").is_none());
+}
+
+#[test]
+fn failed_lookups_do_not_discard_resolved_pins() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    let path = ctx.local_mcp_path();
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    local["mcpServers"]["py"] = serde_json::json!({ "command": "uvx", "args": ["py-tool"] });
+    std::fs::write(&path, serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    // The test registry refuses every PyPI lookup.
+    assert_eq!(lock(&ctx, &[], "1.0.6"), ["@upstash/context7-mcp@1.0.6"]);
+    let lockfile = std::fs::read_to_string(ctx.work_dir().join(".mcp.lock")).unwrap();
+    assert!(lockfile.contains("\"context7\""), "{lockfile}");
+    assert!(!lockfile.contains("\"py\""), "{lockfile}");
+}
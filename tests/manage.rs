@@ -1,6 +1,7 @@
 mod common;
 
 use common::TestContext;
+use predicates::prelude::*;
 
 #[test]
 fn add_and_remove_servers_updates_local_catalogue() {
@@ -24,3 +25,36 @@ fn add_and_remove_servers_updates_local_catalogue() {
     assert!(!servers_after.contains_key("context7"));
     assert!(servers_after.contains_key("serena"));
 }
+
+#[test]
+fn list_and_command_redact_secrets_unless_asked() {
+    let ctx = TestContext::new();
+    let global = serde_json::json!({
+        "mcpServers": {
+            "tokened": { "command": "srv", "args": ["--token", "ghp_abcdefghijklmnop1234"] },
+            "placeholder": { "command": "srv", "args": ["--auth=${MY_AUTH}", "--level", "info"] }
+        }
+    });
+    std::fs::write(ctx.global_mcp_path(), global.to_string()).unwrap();
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("srv --token ••••••"))
+        .stdout(predicate::str::contains("ghp_abcdefghijklmnop1234").not());
+
+    ctx.cli()
+        .args(["command", "placeholder"])
+        .env("MY_AUTH", "plain-looking-value")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("srv --auth=•••••• --level info"));
+
+    ctx.cli()
+        .args(["--show-secrets", "command", "placeholder"])
+        .env("MY_AUTH", "plain-looking-value")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--auth=plain-looking-value"));
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+/// Serve `body` over HTTP once and return the URL it is reachable at.
+fn serve_once(body: String) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/master.json", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    url
+}
+
+fn candidate() -> String {
+    serde_json::json!({
+        "mcpServers": {
+            "context7": { "command": "npx", "args": ["-y", "@upstash/context7-mcp@2"] },
+            "newcomer": { "command": "uvx", "args": ["newcomer-mcp"] }
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn update_from_url_shows_diff_and_installs() {
+    let ctx = TestContext::new();
+    let url = serve_once(candidate());
+
+    ctx.cli()
+        .args(["master", "update", "--from", &url, "--allow-unsigned"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ newcomer"))
+        .stdout(predicate::str::contains("~ context7 (args"))
+        .stdout(predicate::str::contains("- github"));
+
+    let master = std::fs::read_to_string(ctx.master_catalogue_path()).unwrap();
+    assert!(master.contains("newcomer-mcp"));
+
+    // A fresh global catalogue is seeded from the installed master.
+    ctx.cli().arg("list").assert().success().stdout(predicate::str::contains("[newcomer]"));
+
+    ctx.cli()
+        .args(["master", "reset"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- newcomer"));
+    let master = std::fs::read_to_string(ctx.master_catalogue_path()).unwrap();
+    assert!(!master.contains("newcomer-mcp"));
+}
+
+#[test]
+fn update_rejects_bad_pins_and_invalid_catalogues() {
+    let ctx = TestContext::new();
+    let file = ctx.home().join("candidate.json");
+    std::fs::write(&file, candidate()).unwrap();
+    let path = file.to_str().unwrap();
+
+    ctx.cli()
+        .args(["master", "update", "--from", path, "--sha256", "00ff", "--allow-unsigned"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SHA-256 mismatch"));
+
+    use sha2::{Digest, Sha256};
+    let digest = format!("{:x}", Sha256::digest(candidate().as_bytes()));
+    ctx.cli()
+        .args([
+            "master",
+            "update",
+            "--from",
+            path,
+            "--sha256",
+            &digest,
+            "--dry-run",
+            "--allow-unsigned",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dry run"));
+    assert!(!std::fs::read_to_string(ctx.master_catalogue_path()).unwrap().contains("newcomer"));
+
+    std::fs::write(&file, r#"{ "mcpServers": { "broken": { "args": [] } } }"#).unwrap();
+    ctx.cli()
+        .args(["master", "update", "--from", path, "--allow-unsigned"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing-command"))
+        .stderr(predicate::str::contains("failed validation"));
+}
+
+#[test]
+fn corrupt_master_is_quarantined_until_repaired() {
+    let ctx = TestContext::new();
+    let master = ctx.master_catalogue_path();
+    std::fs::create_dir_all(master.parent().unwrap()).unwrap();
+    std::fs::write(&master, "{\n  \"mcpServers\": {\n    \"mine\": { \"command\": \"x\" },\n}")
+        .unwrap();
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("master.json:4:"))
+        .stderr(predicate::str::contains("quarantine"));
+    assert!(!master.exists());
+    let quarantined: Vec<_> =
+        std::fs::read_dir(master.parent().unwrap().join("quarantine")).unwrap().collect();
+    assert_eq!(quarantined.len(), 1);
+
+    // The embedded copy is not silently put back on the next run.
+    ctx.cli().arg("list").assert().failure().stderr(predicate::str::contains("mms master reset"));
+    assert!(!master.exists());
+
+    ctx.cli()
+        .args(["list", "--repair"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Restored the embedded master catalogue"));
+    ctx.cli().arg("list").assert().success();
+}
+
+#[test]
+fn newer_master_schema_is_reported_not_quarantined() {
+    let ctx = TestContext::new();
+    let master = ctx.master_catalogue_path();
+    std::fs::create_dir_all(master.parent().unwrap()).unwrap();
+    let future = r#"{ "mcpServers": {}, "mms": { "schemaVersion": 99 } }"#;
+    std::fs::write(&master, future).unwrap();
+
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("upgrade mms"))
+        .stderr(predicate::str::contains("quarantine").not());
+    assert_eq!(std::fs::read_to_string(&master).unwrap(), future);
+}
+
+#[test]
+fn update_requires_a_trusted_signature() {
+    let ctx = TestContext::new();
+    let file = ctx.home().join("candidate.json");
+    std::fs::write(&file, candidate()).unwrap();
+    let path = file.to_str().unwrap();
+
+    ctx.cli()
+        .args(["master", "update", "--from", path])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("candidate.json.minisig not found"))
+        .stderr(predicate::str::contains("refusing it"));
+
+    std::fs::write(format!("{path}.minisig"), common::minisign(candidate().as_bytes())).unwrap();
+    ctx.cli()
+        .args(["master", "update", "--from", path])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("lists no keys"));
+
+    ctx.trust_test_key();
+    std::fs::write(&file, candidate().replace("newcomer-mcp", "evil-mcp")).unwrap();
+    ctx.cli()
+        .args(["master", "update", "--from", path])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match its signature"));
+    ctx.cli()
+        .args(["master", "update", "--from", path, "--allow-unsigned"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("WARNING"));
+
+    std::fs::write(&file, candidate()).unwrap();
+    ctx.cli()
+        .args(["master", "update", "--from", path])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("signed by trusted key"));
+    assert!(std::fs::read_to_string(ctx.master_catalogue_path()).unwrap().contains("newcomer-mcp"));
+}
+
+#[test]
+fn installed_master_is_verified_on_every_load() {
+    let ctx = TestContext::new();
+    ctx.trust_test_key();
+    let file = ctx.home().join("candidate.json");
+    std::fs::write(&file, candidate()).unwrap();
+    std::fs::write(format!("{}.minisig", file.display()), common::minisign(candidate().as_bytes()))
+        .unwrap();
+    let path = file.to_str().unwrap();
+    ctx.cli().args(["master", "update", "--from", path]).assert().success();
+    ctx.cli().arg("list").assert().success().stdout(predicate::str::contains("[newcomer]"));
+
+    // Editing the installed copy breaks its signature.
+    let master = ctx.master_catalogue_path();
+    let tampered = std::fs::read_to_string(&master).unwrap().replace("newcomer-mcp", "evil-mcp");
+    std::fs::write(&master, tampered).unwrap();
+    std::fs::remove_file(ctx.global_mcp_path()).unwrap();
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("master.json does not match its signature"));
+
+    // An unsigned master accepted once keeps warning on every load.
+    std::fs::remove_file(format!("{path}.minisig")).unwrap();
+    ctx.cli().args(["master", "update", "--from", path, "--allow-unsigned"]).assert().success();
+    ctx.cli().arg("list").assert().success().stderr(predicate::str::contains("WARNING"));
+
+    ctx.cli().args(["master", "reset"]).assert().success();
+    ctx.cli().arg("list").assert().success().stderr(predicate::str::contains("WARNING").not());
+}
+
+#[test]
+fn masters_installed_by_an_earlier_mms_stay_usable() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("list").assert().success();
+    let master = ctx.master_catalogue_path();
+    let marker = master.with_file_name("master.embedded");
+    assert!(marker.exists());
+
+    // What an earlier release would have left behind: its own embedded copy, unsigned.
+    let mut older: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&master).unwrap()).unwrap();
+    older["mcpServers"].as_object_mut().unwrap().remove("context7");
+    let older = serde_json::to_string_pretty(&older).unwrap();
+    std::fs::write(&master, &older).unwrap();
+    std::fs::write(&marker, format!("{:x}\n", Sha256::digest(older.as_bytes()))).unwrap();
+    std::fs::remove_file(ctx.global_mcp_path()).unwrap();
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("run `mms master reset`"));
+
+    // Releases from before the marker are pointed at the fix rather than at signing keys.
+    std::fs::remove_file(&marker).unwrap();
+    std::fs::remove_file(ctx.global_mcp_path()).unwrap();
+    ctx.cli()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run `mms master reset`"));
+    ctx.cli().args(["master", "reset"]).assert().success();
+    ctx.cli().arg("list").assert().success().stderr(predicate::str::contains("earlier mms").not());
+}
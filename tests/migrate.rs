@@ -0,0 +1,68 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn migrate_upgrades_unversioned_catalogues() {
+    let ctx = TestContext::new();
+    let legacy = r#"{ "mcpServers": { "tool": { "command": "tool-mcp" } } }"#;
+    std::fs::write(ctx.local_mcp_path(), legacy).unwrap();
+
+    ctx.cli()
+        .args(["migrate", "--check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("uses schema version 0"));
+    assert_eq!(std::fs::read_to_string(ctx.local_mcp_path()).unwrap(), legacy);
+
+    ctx.cli().arg("migrate").assert().success().stdout(predicate::str::contains("Migrated"));
+
+    let upgraded: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.local_mcp_path()).unwrap()).unwrap();
+    assert_eq!(upgraded["mms"]["schemaVersion"], 1);
+    assert_eq!(upgraded["mcpServers"]["tool"]["command"], "tool-mcp");
+
+    ctx.cli().args(["migrate", "--check"]).assert().success();
+}
+
+#[test]
+fn reading_an_old_catalogue_leaves_the_file_alone() {
+    let ctx = TestContext::new();
+    let legacy = r#"{ "mcpServers": { "tool": { "command": "tool-mcp" } } }"#;
+    std::fs::write(ctx.local_mcp_path(), legacy).unwrap();
+
+    ctx.cli().arg("list").assert().success();
+
+    assert_eq!(std::fs::read_to_string(ctx.local_mcp_path()).unwrap(), legacy);
+}
+
+#[test]
+fn saving_an_old_catalogue_upgrades_it() {
+    let ctx = TestContext::new();
+    std::fs::write(ctx.local_mcp_path(), r#"{ "mcpServers": {} }"#).unwrap();
+
+    ctx.cli().args(["add", "context7"]).assert().success();
+
+    let local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.local_mcp_path()).unwrap()).unwrap();
+    assert_eq!(local["mms"]["schemaVersion"], 1);
+    assert!(local["mcpServers"]["context7"].is_object());
+}
+
+#[test]
+fn migrate_never_rewrites_the_master() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("list").assert().success();
+    let master = ctx.master_catalogue_path();
+    let before = std::fs::read(&master).unwrap();
+
+    ctx.cli().arg("migrate").assert().success();
+    assert_eq!(std::fs::read(&master).unwrap(), before);
+    ctx.cli()
+        .arg("migrate")
+        .arg(&master)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is never rewritten"));
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn search_ranks_matches_and_marks_project_entries() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+
+    ctx.cli()
+        .args(["search", "upstash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[context7] (project) ✅ in .mcp.json"))
+        .stdout(predicate::str::contains("matched package '@upstash/context7-mcp'"));
+
+    let output = ctx.cli().args(["search", "githb"]).assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.starts_with("[github] (master)\n"), "unexpected ranking:\n{stdout}");
+    assert!(!stdout.contains("in .mcp.json"));
+
+    ctx.cli()
+        .args(["search", "no-such-server"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No MCP servers match 'no-such-server'"));
+}
+
+#[test]
+fn search_includes_team_layers() {
+    let ctx = TestContext::new();
+    let team = ctx.home().join("team.json");
+    std::fs::write(
+        &team,
+        r#"{ "mcpServers": { "wiki": { "command": "npx", "args": ["@acme/wiki-mcp"],
+             "description": "Internal wiki", "tags": ["confluence"] } } }"#,
+    )
+    .unwrap();
+    ctx.write_settings(&format!("[[teams]]\nname = \"acme\"\npath = \"{}\"\n", team.display()));
+    ctx.sign(&team);
+
+    ctx.cli()
+        .args(["search", "confluence"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[wiki] (team:acme)"))
+        .stdout(predicate::str::contains("matched tag 'confluence'"));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+const PASSPHRASE: &str = "correct horse battery staple";
+
+#[test]
+fn secrets_round_trip_through_the_encrypted_store() {
+    let ctx = TestContext::new();
+    let secret = |args: &[&str]| {
+        let mut cmd = ctx.cli();
+        cmd.arg("secret").args(args).env("MMS_SECRET_PASSPHRASE", PASSPHRASE);
+        cmd
+    };
+
+    secret(&["set", "github"]).write_stdin("ghp_stored\n").assert().success();
+    secret(&["set", "npm", "--value", "npm_token"]).assert().success();
+
+    let store = ctx.home().join(".config").join("mms").join("secrets.enc");
+    let contents = std::fs::read_to_string(&store).unwrap();
+    assert!(!contents.contains("ghp_stored"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&store).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    secret(&["list"]).assert().success().stdout("github\nnpm\n");
+    secret(&["get", "github"]).assert().success().stdout("ghp_stored\n");
+    secret(&["rm", "npm"]).assert().success();
+    secret(&["get", "npm"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No secret named 'npm'"));
+
+    ctx.cli()
+        .args(["secret", "get", "github"])
+        .env("MMS_SECRET_PASSPHRASE", "wrong")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong passphrase or key file"));
+}
+
+#[test]
+fn secret_placeholders_fall_back_to_the_store() {
+    let ctx = TestContext::new();
+    let key_file = ctx.home().join("mms.key");
+    std::fs::write(&key_file, "0123456789abcdef").unwrap();
+    ctx.write_settings(&format!(
+        "[placeholders]\nresolve = \"late\"\n\n[secret_store]\nkey_file = \"{}\"\n",
+        key_file.display()
+    ));
+
+    ctx.cli().args(["secret", "set", "github", "--value", "ghp_from_store"]).assert().success();
+
+    let catalogue = serde_json::json!({
+        "mcpServers": { "gh": { "command": "gh-mcp", "env": { "TOKEN": "${secret:github}" } } }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+
+    let settings =
+        std::fs::read_to_string(ctx.work_dir().join(".gemini").join("settings.json")).unwrap();
+    assert!(settings.contains("\"TOKEN\": \"ghp_from_store\""));
+
+    let missing = serde_json::json!({
+        "mcpServers": { "gh": { "command": "gh-mcp", "env": { "TOKEN": "${secret:absent}" } } }
+    });
+    std::fs::write(ctx.local_mcp_path(), missing.to_string()).unwrap();
+    // Pointing a server at another secret is a definition change like any other.
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("+ providers: ${secret:absent}"));
+    ctx.cli().args(["trust", "gh"]).assert().success();
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("secret 'absent' is neither mapped"));
+}
+
+#[test]
+fn show_leaves_the_secret_store_locked() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .args(["secret", "set", "API_TOKEN", "--value", "stored_token"])
+        .env("MMS_SECRET_PASSPHRASE", PASSPHRASE)
+        .assert()
+        .success();
+
+    let catalogue = serde_json::json!({
+        "mcpServers": { "gh": { "command": "gh-mcp", "env": { "TOKEN": "${API_TOKEN}" } } }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+    // Without a passphrase, unlocking the store would fail; `show` must not try.
+    ctx.cli()
+        .args(["-v", "show", "gh"])
+        .env("MMS_SECRET_PASSPHRASE", "wrong")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✗ not set (secret store not checked)"))
+        .stdout(predicate::str::contains("Variable API_TOKEN ← (not set)"))
+        .stderr(predicate::str::contains("wrong passphrase").not());
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn show_reports_definition_variables_scopes_and_targets() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+
+    ctx.cli()
+        .args(["show", "context7"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[context7] (project)"))
+        .stdout(predicate::str::contains("\"@upstash/context7-mcp@latest\""))
+        .stdout(predicate::str::contains("Command:    npx -y @upstash/context7-mcp@latest"))
+        .stdout(predicate::str::contains("Variables:  none"))
+        .stdout(
+            predicate::str::is_match(r"project +\S+\.mcp\.json +same as the definition").unwrap(),
+        )
+        .stdout(predicate::str::is_match(r"Gemini +\S+settings\.json +✅ included").unwrap());
+
+    // A project edit makes the master copy differ from what is in effect.
+    let path = ctx.local_mcp_path();
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    local["mcpServers"]["context7"]["args"] = serde_json::json!(["-y", "context7@2"]);
+    local["mcpServers"]["context7"]["env"] = serde_json::json!({ "C7_TOKEN": "${C7_TOKEN}" });
+    std::fs::write(&path, serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    ctx.cli()
+        .args(["show", "context7"])
+        .env_remove("C7_TOKEN")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"C7_TOKEN +required +✗ not set +env\.C7_TOKEN").unwrap())
+        .stdout(
+            predicate::str::is_match(r"master +\S+master\.json +differs in args, env").unwrap(),
+        );
+}
+
+#[test]
+fn show_lists_servers_missing_from_targets() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("init").assert().success();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+
+    ctx.cli()
+        .args(["show", "context7"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[context7] (master)"))
+        .stdout(predicate::str::is_match(r"Gemini +\S+ +– not included").unwrap());
+    ctx.cli()
+        .args(["show", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MCP server 'nope' not found"));
+}
+
+#[test]
+fn show_never_runs_providers() {
+    let ctx = TestContext::new();
+    let marker = ctx.home().join("ran");
+    let catalogue = serde_json::json!({ "mcpServers": { "scripted": { "command": "scripted",
+        "args": [format!("--token=${{cmd:touch {}}}", marker.display())] } } });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+
+    ctx.cli()
+        .args(["show", "scripted"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Command:    scripted \"--token=${cmd:touch"))
+        .stdout(predicate::str::is_match(r"cmd:touch \S+ +provider +✓ shell command").unwrap());
+    assert!(!marker.exists());
+}
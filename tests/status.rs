@@ -0,0 +1,101 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn status_reports_provenance_and_stale_targets() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Catalogue:  none"))
+        .stdout(predicate::str::contains("not installed yet"));
+
+    ctx.cli().args(["init"]).assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+    ctx.cli()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(ctx.local_mcp_path().display().to_string()))
+        .stdout(predicate::str::contains("✅ matches mms"))
+        .stdout(predicate::str::contains("✅ up to date with the master"))
+        .stdout(predicate::str::contains("1 copied entry unchanged at the source"))
+        .stdout(predicate::str::contains("✅ current"))
+        .stdout(predicate::str::contains("all required variables are available"));
+
+    // A new master leaves the global catalogue, the copy and the synced target behind.
+    let candidate = ctx.home().join("candidate.json");
+    std::fs::write(
+        &candidate,
+        r#"{ "mcpServers": { "context7": { "command": "npx", "args": ["context7@2"] } } }"#,
+    )
+    .unwrap();
+    ctx.cli()
+        .args(["master", "update", "--from", candidate.to_str().unwrap(), "--allow-unsigned"])
+        .assert()
+        .success();
+    ctx.cli().args(["global", "upgrade"]).assert().success();
+    let local = std::fs::read_to_string(ctx.local_mcp_path()).unwrap();
+    std::fs::write(ctx.local_mcp_path(), local.replace("context7-mcp@latest", "edited")).unwrap();
+
+    ctx.cli()
+        .arg("status")
+        .env_remove("MMS_GITHUB_PAT")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("installed from another source"))
+        .stdout(predicate::str::contains("✅ up to date with the master"))
+        .stdout(predicate::str::contains("changed at the source since copied: context7"))
+        .stdout(predicate::str::contains("out of date; run `mms sync`"));
+}
+
+#[test]
+fn status_lists_missing_variables() {
+    let ctx = TestContext::new();
+    ctx.write_settings("[placeholders]\nresolve = \"late\"\n");
+    ctx.cli().args(["init"]).assert().success();
+    ctx.cli().args(["add", "github"]).env("MMS_GITHUB_PAT", "ghp_status").assert().success();
+
+    ctx.cli()
+        .arg("status")
+        .env_remove("MMS_GITHUB_PAT")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("MMS_GITHUB_PAT (needed by github)"))
+        // `sync` passes a plain `${VAR}` through, so the targets can still be compared.
+        .stdout(predicate::str::contains("not synced yet"));
+
+    std::fs::write(
+        ctx.local_mcp_path(),
+        r#"{ "mcpServers": { "one": { "command": "one", "env": { "A": "${NEEDED:?set me}" } } } }"#,
+    )
+    .unwrap();
+    ctx.cli()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cannot tell until the missing variables are set"));
+}
+
+#[test]
+fn status_changes_nothing_and_runs_no_providers() {
+    let ctx = TestContext::new();
+    let marker = ctx.home().join("ran");
+    let legacy = serde_json::json!({ "mcpServers": { "scripted": { "command": "scripted",
+        "env": { "TOKEN": format!("${{cmd:touch {}}}", marker.display()) } } } })
+    .to_string();
+    std::fs::write(ctx.local_mcp_path(), &legacy).unwrap();
+
+    ctx.cli()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cannot tell without running provider commands"));
+
+    assert!(!marker.exists());
+    assert_eq!(std::fs::read_to_string(ctx.local_mcp_path()).unwrap(), legacy);
+}
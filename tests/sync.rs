@@ -28,3 +28,140 @@ fn sync_updates_gemini_and_codex() {
     assert!(codex_updated.contains("[mcp_servers.context7]"));
     assert!(codex_updated.contains("command"));
 }
+
+#[test]
+fn sync_resolves_linked_entries_against_global() {
+    let ctx = TestContext::new();
+
+    ctx.cli().arg("init").assert().success();
+    ctx.cli()
+        .args(["add", "--link", "github"])
+        .env("MMS_GITHUB_PAT", "ghp_test")
+        .assert()
+        .success();
+
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.local_mcp_path()).unwrap()).unwrap();
+    assert_eq!(local["mcpServers"]["github"], serde_json::json!({ "extends": "github" }));
+
+    // Override one env var locally and change the global entry afterwards.
+    local["mcpServers"]["github"]["env"] = serde_json::json!({ "GITHUB_TOOLSETS": "repos" });
+    std::fs::write(ctx.local_mcp_path(), serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    let mut global: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(ctx.global_mcp_path()).unwrap()).unwrap();
+    global["mcpServers"]["github"]["args"] = serde_json::json!(["-y", "github-mcp@2"]);
+    std::fs::write(ctx.global_mcp_path(), serde_json::to_string_pretty(&global).unwrap()).unwrap();
+
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+
+    let settings: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(ctx.work_dir().join(".gemini").join("settings.json")).unwrap(),
+    )
+    .unwrap();
+    let github = &settings["mcpServers"]["github"];
+    assert_eq!(github["command"], "npx");
+    assert_eq!(github["args"], serde_json::json!(["-y", "github-mcp@2"]));
+    assert_eq!(github["env"]["GITHUB_TOOLSETS"], "repos");
+    assert!(github["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"].is_string());
+    assert!(github.get("extends").is_none());
+}
+
+#[cfg(unix)]
+fn mode_of(path: &std::path::Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[cfg(unix)]
+#[test]
+fn late_placeholders_stay_out_of_catalogues_until_sync() {
+    let ctx = TestContext::new();
+    ctx.write_settings("[placeholders]\nresolve = \"late\"\n");
+    std::process::Command::new("git").arg("init").arg("-q").arg(ctx.work_dir()).status().unwrap();
+
+    ctx.cli().args(["init", "--from-global"]).env("MMS_GITHUB_PAT", "ghp_late").assert().success();
+
+    let global = std::fs::read_to_string(ctx.global_mcp_path()).unwrap();
+    assert!(global.contains("${MMS_GITHUB_PAT}"));
+    assert!(!std::fs::read_to_string(ctx.local_mcp_path()).unwrap().contains("ghp_late"));
+
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .env("MMS_GITHUB_PAT", "ghp_late")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("not ignored; writing resolved secrets to it"));
+
+    let settings_path = ctx.work_dir().join(".gemini").join("settings.json");
+    assert!(std::fs::read_to_string(&settings_path).unwrap().contains("ghp_late"));
+    assert_eq!(mode_of(&settings_path), 0o600);
+}
+
+#[cfg(unix)]
+#[test]
+fn eagerly_resolved_catalogues_are_private() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["init", "--from-global"]).env("MMS_GITHUB_PAT", "ghp_eager").assert().success();
+
+    assert_eq!(mode_of(&ctx.global_mcp_path()), 0o600);
+    assert_eq!(mode_of(&ctx.local_mcp_path()), 0o600);
+}
+
+#[test]
+fn native_mode_writes_target_variable_references() {
+    let ctx = TestContext::new();
+    ctx.write_settings("[placeholders]\nresolve = \"native\"\n");
+    let catalogue = serde_json::json!({
+        "mcpServers": {
+            "api": {
+                "command": "api-mcp",
+                "args": ["--region=${API_REGION:-us}"],
+                "env": { "API_TOKEN": "${API_TOKEN}" },
+                // Only passthroughs derived from `env` reach Codex, never a hand-written list.
+                "env_vars": ["AWS_SECRET_ACCESS_KEY"]
+            }
+        }
+    });
+    std::fs::write(ctx.local_mcp_path(), catalogue.to_string()).unwrap();
+    let codex_config = ctx.home().join(".codex").join("config.toml");
+    std::fs::create_dir_all(codex_config.parent().unwrap()).unwrap();
+    std::fs::write(&codex_config, "").unwrap();
+
+    ctx.cli()
+        .arg("sync")
+        .env("API_TOKEN", "secret-token-value")
+        .env("API_REGION", "eu")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("has no native form").count(2));
+
+    let gemini =
+        std::fs::read_to_string(ctx.work_dir().join(".gemini").join("settings.json")).unwrap();
+    assert!(gemini.contains("\"API_TOKEN\": \"$API_TOKEN\""));
+    assert!(gemini.contains("--region=eu"));
+    assert!(!gemini.contains("secret-token-value"));
+
+    let codex = std::fs::read_to_string(&codex_config).unwrap();
+    assert!(codex.contains("env_vars = [\"API_TOKEN\"]"));
+    assert!(!codex.contains("AWS_SECRET_ACCESS_KEY"));
+    assert!(!codex.contains("secret-token-value"));
+}
+
+#[test]
+fn eager_global_catalogues_keep_provider_placeholders() {
+    let ctx = TestContext::new();
+    let marker = ctx.home().join("ran");
+    let master = ctx.master_catalogue_path();
+    std::fs::create_dir_all(master.parent().unwrap()).unwrap();
+    let catalogue = serde_json::json!({ "mcpServers": { "tool": {
+        "command": "tool", "env": { "TOKEN": format!("${{cmd:touch {}}}", marker.display()) } } } });
+    std::fs::write(&master, catalogue.to_string()).unwrap();
+    ctx.sign(&master);
+
+    ctx.cli().arg("list").assert().success();
+
+    assert!(!marker.exists());
+    assert!(std::fs::read_to_string(ctx.global_mcp_path()).unwrap().contains("${cmd:touch"));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn changed_definitions_need_approval_before_sync() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["init"]).assert().success();
+    ctx.cli().args(["add", "context7"]).assert().success();
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Approved on first use: context7"));
+
+    // Someone edits the shared project catalogue to run a different package.
+    let path = ctx.local_mcp_path();
+    let mut local: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    local["mcpServers"]["context7"]["args"] = serde_json::json!(["-y", "evil-package"]);
+    std::fs::write(&path, serde_json::to_string_pretty(&local).unwrap()).unwrap();
+
+    ctx.cli()
+        .args(["sync", "--skip-codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'context7' changed since it was approved"))
+        .stderr(predicate::str::contains("+ args: -y evil-package"))
+        .stderr(predicate::str::contains("mms trust context7"));
+
+    ctx.cli()
+        .args(["trust", "context7"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ args: -y evil-package"))
+        .stdout(predicate::str::contains("Approved 'context7'"));
+    ctx.cli().args(["sync", "--skip-codex"]).assert().success();
+    ctx.cli()
+        .args(["trust", "context7"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already approved"));
+}
+
+#[test]
+fn trust_rejects_unknown_servers() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .args(["trust", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MCP server 'nope' not found"));
+}
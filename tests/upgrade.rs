@@ -0,0 +1,67 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+fn read_json(path: &std::path::Path) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn write_json(path: &std::path::Path, value: &serde_json::Value) {
+    std::fs::write(path, serde_json::to_string_pretty(value).unwrap()).unwrap();
+}
+
+#[test]
+fn upgrade_merges_master_changes_and_keeps_local_edits() {
+    let ctx = TestContext::new();
+    ctx.cli().arg("list").env("MMS_GITHUB_PAT", "ghp_upgrade").assert().success();
+
+    // Local edits: a custom server and a changed context7 description.
+    let mut global = read_json(&ctx.global_mcp_path());
+    global["mcpServers"]["mine"] = serde_json::json!({ "command": "my-server" });
+    global["mcpServers"]["context7"]["description"] = serde_json::json!("my notes");
+    global["mcpServers"]["github"]["args"] = serde_json::json!(["local-arg"]);
+    write_json(&ctx.global_mcp_path(), &global);
+
+    // New master: changes context7 args, github args, adds a server.
+    let mut master = read_json(&ctx.master_catalogue_path());
+    master["mcpServers"]["context7"]["args"] = serde_json::json!(["-y", "context7@2"]);
+    master["mcpServers"]["github"]["args"] = serde_json::json!(["github@2"]);
+    master["mcpServers"]["fresh"] = serde_json::json!({ "command": "fresh-mcp" });
+    write_json(&ctx.master_catalogue_path(), &master);
+    ctx.sign(&ctx.master_catalogue_path());
+
+    ctx.cli()
+        .args(["global", "upgrade", "--dry-run"])
+        .env("MMS_GITHUB_PAT", "ghp_upgrade")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ fresh"))
+        .stdout(predicate::str::contains("~ context7 args"))
+        .stdout(predicate::str::contains("Conflict in 'github' args"));
+    assert!(read_json(&ctx.global_mcp_path())["mcpServers"].get("fresh").is_none());
+
+    ctx.cli()
+        .args(["global", "upgrade"])
+        .env("MMS_GITHUB_PAT", "ghp_upgrade")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept local values for 1 conflict(s): 'github' args"));
+
+    let upgraded = read_json(&ctx.global_mcp_path());
+    let servers = &upgraded["mcpServers"];
+    assert_eq!(servers["context7"]["args"], serde_json::json!(["-y", "context7@2"]));
+    assert_eq!(servers["context7"]["description"], "my notes");
+    assert_eq!(servers["github"]["args"], serde_json::json!(["local-arg"]));
+    assert_eq!(servers["github"]["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_upgrade");
+    assert_eq!(servers["mine"]["command"], "my-server");
+    assert_eq!(servers["fresh"]["command"], "fresh-mcp");
+
+    // The base snapshot now matches the master, so a second run has nothing to do.
+    ctx.cli()
+        .args(["global", "upgrade"])
+        .env("MMS_GITHUB_PAT", "ghp_upgrade")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already includes every master change"));
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+
+#[test]
+fn validate_reports_locations_and_blocks_sync() {
+    let ctx = TestContext::new();
+    let catalogue =
+        "{\n  \"mcpServers\": {\n    \"tool\": { \"command\": \"\", \"argz\": [] }\n  }\n}\n";
+    std::fs::write(ctx.local_mcp_path(), catalogue).unwrap();
+
+    ctx.cli()
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(".mcp.json:3:26: error[empty-command]"))
+        .stderr(predicate::str::contains("warning[unknown-key]"))
+        .stderr(predicate::str::contains("did you mean \"args\"?"));
+
+    ctx.cli().args(["sync", "--skip-codex"]).assert().failure();
+    assert!(!ctx.work_dir().join(".gemini").join("settings.json").exists());
+}
+
+#[test]
+fn validate_accepts_catalogue_from_global() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["init", "--from-global"]).assert().success();
+
+    ctx.cli().arg("validate").assert().success().stdout(predicate::str::contains("is valid"));
+}
+
+#[test]
+fn maintenance_commands_work_with_a_broken_config() {
+    let ctx = TestContext::new();
+    ctx.write_settings("[placeholders\nresolve = ");
+    std::fs::write(ctx.local_mcp_path(), r#"{ "mcpServers": {} }"#).unwrap();
+
+    ctx.cli().arg("schema").assert().success();
+    ctx.cli().arg("validate").assert().success();
+    ctx.cli().arg("migrate").assert().success();
+    ctx.cli().args(["clean", "--local", "--dry-run"]).assert().success();
+    ctx.cli().arg("list").assert().failure().stderr(predicate::str::contains("TOML parse error"));
+}